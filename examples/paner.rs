@@ -24,12 +24,19 @@ fn main() {
                     (Relative(1), Pane(Special)),
                 ])),
                 (Fixed(1), Pane(Special)),
-                (Relative(1), Pane(Normal)),
+                (Relative(1), Pane(Normal).framed(Frame::new()
+                    .title("Logs")
+                    .footer(Label::new("3 errors").align(Align::Right))
+                )),
                 (Relative(1), Pane(Normal)),
             ])),
             (Relative(1), Vertical(vec![
                 (Relative(1), Pane(Normal)),
-                (Relative(2), Pane(Special)),
+                // highlight a pane with a heavy, colored border
+                (Relative(2), Pane(Special).framed(Frame::new()
+                    .border(BorderStyle::CONNECTED_HEAVY)
+                    .style(Style::new().fg(BrightYellow))
+                )),
             ])),
         ])
     };
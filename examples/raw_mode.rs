@@ -1,5 +1,4 @@
 use termabc::prelude::*;
-use defer_rs::defer;
 
 fn run() -> Result<(), Box<dyn std::error::Error>> {
     print!("waiting for input...");
@@ -34,15 +33,11 @@ fn run() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn main() {
-    // hide cursor
-    print!("{CUR_HIDE}");
-    init_term().unwrap();
-
-    // restore the terminal when the code finishes or crashes
-    defer! {
-        let _ = restore_term();
-    };
+    // enter raw mode on the alternate screen with the cursor hidden,
+    // which is restored when the code finishes or crashes
+    let terminal = Terminal::new().unwrap();
 
     let res = run();
+    drop(terminal);
     res.expect("error occured");
 }
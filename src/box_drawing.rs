@@ -0,0 +1,86 @@
+use crate::Static;
+
+pub(crate) const LIGHT: u8 = 1;
+pub(crate) const HEAVY: u8 = 2;
+pub(crate) const DOUBLE: u8 = 3;
+
+/// Box drawing characters, with the weight of the line going up, right, down and left
+const TABLE: &[(Static, [u8; 4])] = &[
+    ("─", [0, 1, 0, 1]), ("━", [0, 2, 0, 2]), ("│", [1, 0, 1, 0]), ("┃", [2, 0, 2, 0]),
+
+    ("┌", [0, 1, 1, 0]), ("┍", [0, 2, 1, 0]), ("┎", [0, 1, 2, 0]), ("┏", [0, 2, 2, 0]),
+    ("┐", [0, 0, 1, 1]), ("┑", [0, 0, 1, 2]), ("┒", [0, 0, 2, 1]), ("┓", [0, 0, 2, 2]),
+    ("└", [1, 1, 0, 0]), ("┕", [1, 2, 0, 0]), ("┖", [2, 1, 0, 0]), ("┗", [2, 2, 0, 0]),
+    ("┘", [1, 0, 0, 1]), ("┙", [1, 0, 0, 2]), ("┚", [2, 0, 0, 1]), ("┛", [2, 0, 0, 2]),
+
+    ("├", [1, 1, 1, 0]), ("┝", [1, 2, 1, 0]), ("┞", [2, 1, 1, 0]), ("┟", [1, 1, 2, 0]),
+    ("┠", [2, 1, 2, 0]), ("┡", [2, 2, 1, 0]), ("┢", [1, 2, 2, 0]), ("┣", [2, 2, 2, 0]),
+    ("┤", [1, 0, 1, 1]), ("┥", [1, 0, 1, 2]), ("┦", [2, 0, 1, 1]), ("┧", [1, 0, 2, 1]),
+    ("┨", [2, 0, 2, 1]), ("┩", [2, 0, 1, 2]), ("┪", [1, 0, 2, 2]), ("┫", [2, 0, 2, 2]),
+    ("┬", [0, 1, 1, 1]), ("┭", [0, 1, 1, 2]), ("┮", [0, 2, 1, 1]), ("┯", [0, 2, 1, 2]),
+    ("┰", [0, 1, 2, 1]), ("┱", [0, 1, 2, 2]), ("┲", [0, 2, 2, 1]), ("┳", [0, 2, 2, 2]),
+    ("┴", [1, 1, 0, 1]), ("┵", [1, 1, 0, 2]), ("┶", [1, 2, 0, 1]), ("┷", [1, 2, 0, 2]),
+    ("┸", [2, 1, 0, 1]), ("┹", [2, 1, 0, 2]), ("┺", [2, 2, 0, 1]), ("┻", [2, 2, 0, 2]),
+
+    ("┼", [1, 1, 1, 1]), ("┽", [1, 1, 1, 2]), ("┾", [1, 2, 1, 1]), ("┿", [1, 2, 1, 2]),
+    ("╀", [2, 1, 1, 1]), ("╁", [1, 1, 2, 1]), ("╂", [2, 1, 2, 1]), ("╃", [2, 1, 1, 2]),
+    ("╄", [2, 2, 1, 1]), ("╅", [1, 1, 2, 2]), ("╆", [1, 2, 2, 1]), ("╇", [2, 2, 1, 2]),
+    ("╈", [1, 2, 2, 2]), ("╉", [2, 1, 2, 2]), ("╊", [2, 2, 2, 1]), ("╋", [2, 2, 2, 2]),
+
+    ("═", [0, 3, 0, 3]), ("║", [3, 0, 3, 0]),
+    ("╒", [0, 3, 1, 0]), ("╓", [0, 1, 3, 0]), ("╔", [0, 3, 3, 0]),
+    ("╕", [0, 0, 1, 3]), ("╖", [0, 0, 3, 1]), ("╗", [0, 0, 3, 3]),
+    ("╘", [1, 3, 0, 0]), ("╙", [3, 1, 0, 0]), ("╚", [3, 3, 0, 0]),
+    ("╛", [1, 0, 0, 3]), ("╜", [3, 0, 0, 1]), ("╝", [3, 0, 0, 3]),
+    ("╞", [1, 3, 1, 0]), ("╟", [3, 1, 3, 0]), ("╠", [3, 3, 3, 0]),
+    ("╡", [1, 0, 1, 3]), ("╢", [3, 0, 3, 1]), ("╣", [3, 0, 3, 3]),
+    ("╤", [0, 3, 1, 3]), ("╥", [0, 1, 3, 1]), ("╦", [0, 3, 3, 3]),
+    ("╧", [1, 3, 0, 3]), ("╨", [3, 1, 0, 1]), ("╩", [3, 3, 0, 3]),
+    ("╪", [1, 3, 1, 3]), ("╫", [3, 1, 3, 1]), ("╬", [3, 3, 3, 3]),
+
+    ("╴", [0, 0, 0, 1]), ("╵", [1, 0, 0, 0]), ("╶", [0, 1, 0, 0]), ("╷", [0, 0, 1, 0]),
+    ("╸", [0, 0, 0, 2]), ("╹", [2, 0, 0, 0]), ("╺", [0, 2, 0, 0]), ("╻", [0, 0, 2, 0]),
+    ("╼", [0, 2, 0, 1]), ("╽", [1, 0, 2, 0]), ("╾", [0, 1, 0, 2]), ("╿", [2, 0, 1, 0]),
+];
+
+/// Characters with the same lines as one in the table, but drawn rounded or dashed
+const ALIASES: &[(Static, Static)] = &[
+    ("╭", "┌"), ("╮", "┐"), ("╯", "┘"), ("╰", "└"),
+    ("┄", "─"), ("┈", "─"), ("╌", "─"), ("┅", "━"), ("┉", "━"), ("╍", "━"),
+    ("┆", "│"), ("┊", "│"), ("╎", "│"), ("┇", "┃"), ("┋", "┃"), ("╏", "┃"),
+];
+
+/// The weight of the lines in a set of border characters, judging by its horizontal line
+pub(crate) fn weight(h: &str) -> Option<u8> {
+    match h {
+        "─" => Some(LIGHT),
+        "━" => Some(HEAVY),
+        "═" => Some(DOUBLE),
+        _ => None,
+    }
+}
+
+/// The character with lines of the given weights going up, right, down and left
+pub(crate) fn from_arms(arms: [u8; 4]) -> Option<Static> {
+    TABLE.iter().find(|(_, i)| *i == arms).map(|(ch, _)| *ch)
+}
+
+/// The weights of the lines of a character going up, right, down and left
+pub(crate) fn arms(ch: &str) -> Option<[u8; 4]> {
+    let ch = ALIASES.iter().find(|(alias, _)| *alias == ch).map_or(ch, |(_, ch)| *ch);
+    TABLE.iter().find(|(i, _)| *i == ch).map(|(_, arms)| *arms)
+}
+
+/// The character to draw where a line is drawn over another, joining them if possible
+pub(crate) fn merge<'a>(below: &str, above: &'a str) -> &'a str {
+    let (Some(below), Some(arms)) = (arms(below), arms(above)) else {
+        return above
+    };
+
+    let merged = std::array::from_fn(|i| arms[i].max(below[i]));
+    if merged == arms {
+        return above
+    }
+
+    from_arms(merged).unwrap_or(above)
+}
@@ -0,0 +1,419 @@
+use crate::*;
+use crate::utils::{display_width, truncate};
+
+/// Blocks filling the bottom of a cell by eighths
+const VERTICAL_BLOCKS: [Static; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+/// Blocks filling the left of a cell by eighths
+pub(crate) const HORIZONTAL_BLOCKS: [Static; 9] = [" ", "▏", "▎", "▍", "▌", "▋", "▊", "▉", "█"];
+
+/// Format a value for a label, without trailing zeros
+fn format_value(value: f64) -> Str {
+    let string = format!("{value:.2}");
+    string.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The amount of eighths of a cell a value fills, out of some amount of cells
+fn eighths(value: f64, max: f64, cells: Cell) -> u32 {
+    if max <= 0.0 || !value.is_finite() {
+        return 0
+    }
+
+    ((value / max).clamp(0.0, 1.0) * cells as f64 * 8.0).round() as u32
+}
+
+/// Draw a bar going up from the bottom of an area
+fn draw_column(canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell, width: Cell, height: Cell,
+        eighths: u32, style: Option<&Style>) {
+
+    for i in 0..height {
+        let filled = eighths.saturating_sub(i as u32 * 8).min(8) as usize;
+        canvas.draw_hbar(col, row + height - 1 - i, width, VERTICAL_BLOCKS[filled], style);
+    }
+}
+
+/// Values shown as a row of bars, without any labels
+pub struct Sparkline<'a> {
+    data: &'a [f64],
+    max: Option<f64>,
+    style: Option<Style>,
+}
+
+impl<'a> Sparkline<'a> {
+    pub fn new(data: &'a [f64]) -> Self {
+        Self {
+            data,
+            max: None,
+            style: None,
+        }
+    }
+
+    /// The value of a full bar, which is otherwise the highest value
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Draw the last values that fit in the width, one per column
+    pub fn render(&self, canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell, width: Cell, height: Cell) {
+        let data = &self.data[self.data.len().saturating_sub(width.into())..];
+        let max = self.max.unwrap_or_else(|| data.iter().copied().fold(0.0, f64::max));
+
+        for (i, value) in data.iter().enumerate() {
+            let eighths = eighths(*value, max, height);
+            draw_column(canvas, col + i as Cell, row, 1, height, eighths, self.style.as_ref());
+        }
+    }
+}
+
+/// Labeled values shown as bars next to or above each other
+pub struct BarChart {
+    bars: Vec<(Str, f64)>,
+    vertical: bool,
+    max: Option<f64>,
+    bar_width: Option<Cell>,
+    gap: Cell,
+    style: Option<Style>,
+}
+
+impl BarChart {
+    /// Bars going right, one per row, with the label before and the value after the bar
+    pub fn horizontal(bars: Vec<(Str, f64)>) -> Self {
+        Self {
+            bars,
+            vertical: false,
+            max: None,
+            bar_width: Some(1),
+            gap: 0,
+            style: None,
+        }
+    }
+
+    /// Bars going up, with the label below the bar
+    pub fn vertical(bars: Vec<(Str, f64)>) -> Self {
+        Self {
+            bars,
+            vertical: true,
+            max: None,
+            bar_width: None,
+            gap: 1,
+            style: None,
+        }
+    }
+
+    /// The value of a full bar, which is otherwise the highest value
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Thickness of the bars, which is otherwise decided by the space available
+    pub fn bar_width(mut self, width: Cell) -> Self {
+        self.bar_width = Some(width.max(1));
+        self
+    }
+
+    /// Space between bars
+    pub fn gap(mut self, gap: Cell) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn render(&self, canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell, width: Cell, height: Cell) {
+        if self.bars.is_empty() || width == 0 || height == 0 {
+            return
+        }
+
+        let max = self.max.unwrap_or_else(|| self.bars.iter().map(|(_, value)| *value).fold(0.0, f64::max));
+        let count = self.bars.len() as Cell;
+        let space = if self.vertical { width } else { height };
+        let bar_width = self.bar_width
+            .unwrap_or_else(|| (space.saturating_add(self.gap) / count).saturating_sub(self.gap))
+            .max(1);
+
+        for (i, (label, value)) in self.bars.iter().enumerate() {
+            let offset = i as Cell * (bar_width + self.gap);
+            if offset + bar_width > space {
+                break
+            }
+
+            if self.vertical {
+                let bar_height = height - 1;
+                let eighths = eighths(*value, max, bar_height);
+                draw_column(canvas, col + offset, row, bar_width, bar_height, eighths, self.style.as_ref());
+                canvas.addstr(col + offset, row + bar_height, &truncate(label, bar_width), None);
+            } else {
+                self.draw_row(canvas, col, row + offset, width, bar_width, label, *value, max);
+            }
+        }
+    }
+
+    /// Draw a horizontal bar with its label and value
+    #[allow(clippy::too_many_arguments)]
+    fn draw_row(&self, canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell, width: Cell,
+            bar_width: Cell, label: &str, value: f64, max: f64) {
+
+        let label_width = self.bars.iter()
+            .map(|(label, _)| display_width(label) as Cell)
+            .max().unwrap_or(0)
+            .min(width / 3);
+        let value_width = self.bars.iter()
+            .map(|(_, value)| format_value(*value).len() as Cell)
+            .max().unwrap_or(0);
+        let length = width.saturating_sub(label_width + value_width + 2);
+
+        let eighths = eighths(value, max, length);
+        let (full, partial) = ((eighths / 8) as usize, (eighths % 8) as usize);
+        let mut bar = HORIZONTAL_BLOCKS[8].repeat(full);
+        if partial > 0 {
+            bar.push_str(HORIZONTAL_BLOCKS[partial]);
+        }
+
+        for i in 0..bar_width {
+            if i == bar_width / 2 {
+                canvas.addstr(col, row + i, &truncate(label, label_width), None);
+                let value_col = col + label_width + 1 + display_width(&bar) as Cell + 1;
+                canvas.addstr(value_col, row + i, &format_value(value), None);
+            }
+            canvas.addstr(col + label_width + 1, row + i, &bar, self.style.as_ref());
+        }
+    }
+}
+
+/// Points of a `LineChart`
+pub struct Dataset {
+    pub points: Vec<(f64, f64)>,
+    /// Shown in the legend, unless empty
+    pub name: Str,
+    pub color: Color,
+    /// Only draw the points, without lines between them
+    pub scatter: bool,
+}
+
+impl Dataset {
+    pub fn new(points: Vec<(f64, f64)>) -> Self {
+        Self {
+            points,
+            name: Str::new(),
+            color: Color::Default,
+            scatter: false,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<Str>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn scatter(mut self) -> Self {
+        self.scatter = true;
+        self
+    }
+}
+
+/// Datasets drawn with braille dots, between labeled axes
+pub struct LineChart {
+    datasets: Vec<Dataset>,
+    x_bounds: Option<(f64, f64)>,
+    y_bounds: Option<(f64, f64)>,
+    style: Option<Style>,
+}
+
+impl LineChart {
+    pub fn new(datasets: Vec<Dataset>) -> Self {
+        Self {
+            datasets,
+            x_bounds: None,
+            y_bounds: None,
+            style: None,
+        }
+    }
+
+    /// The lowest and highest value on the x axis, which are otherwise taken from the data
+    pub fn x_bounds(mut self, min: f64, max: f64) -> Self {
+        self.x_bounds = Some((min, max));
+        self
+    }
+
+    /// The lowest and highest value on the y axis, which are otherwise taken from the data
+    pub fn y_bounds(mut self, min: f64, max: f64) -> Self {
+        self.y_bounds = Some((min, max));
+        self
+    }
+
+    /// Style of the axes and their labels
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// The bounds of the data along one axis, made wider if they are equal
+    fn bounds(&self, axis: impl Fn(&(f64, f64)) -> f64) -> (f64, f64) {
+        let (min, max) = self.datasets.iter()
+            .flat_map(|dataset| dataset.points.iter().map(&axis))
+            .filter(|value| value.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+
+        match (min, max) {
+            _ if min > max => (0.0, 1.0),
+            _ if min == max => (min - 1.0, max + 1.0),
+            _ => (min, max),
+        }
+    }
+
+    pub fn render(&self, canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell, width: Cell, height: Cell) {
+        let (x_min, x_max) = self.x_bounds.unwrap_or_else(|| self.bounds(|point| point.0));
+        let (y_min, y_max) = self.y_bounds.unwrap_or_else(|| self.bounds(|point| point.1));
+        let style = self.style.as_ref();
+
+        let y_labels = [format_value(y_max), format_value(y_min)];
+        let label_width = y_labels.iter().map(|label| label.len() as Cell).max().unwrap_or(0);
+
+        // the y axis and its labels take up the left, and the x axis and its labels the bottom
+        let plot_col = col + label_width + 1;
+        let plot_width = width.saturating_sub(label_width + 1);
+        let plot_height = height.saturating_sub(2);
+        if plot_width == 0 || plot_height == 0 {
+            return
+        }
+
+        canvas.draw_vbar(plot_col - 1, row, plot_height, "│", style);
+        canvas.addstr(plot_col - 1, row + plot_height, "└", style);
+        canvas.draw_hbar(plot_col, row + plot_height, plot_width, "─", style);
+
+        canvas.addstr(col, row, &format!("{:>1$}", y_labels[0], label_width as usize), style);
+        // with a single row the min label would overwrite the max
+        if plot_height >= 2 {
+            canvas.addstr(col, row + plot_height - 1, &format!("{:>1$}", y_labels[1], label_width as usize), style);
+        }
+
+        let x_max_label = format_value(x_max);
+        let x_max_col = (plot_col + plot_width).saturating_sub(x_max_label.len() as Cell);
+        canvas.addstr(plot_col, row + plot_height + 1, &format_value(x_min), style);
+        canvas.addstr(x_max_col, row + plot_height + 1, &x_max_label, style);
+
+        let mut pixels = PixelCanvas::braille(plot_width, plot_height);
+        let (pixel_width, pixel_height) = (pixels.width() - 1, pixels.height() - 1);
+        let scale = |(x, y): (f64, f64)| {
+            let x = (x - x_min) / (x_max - x_min) * pixel_width as f64;
+            let y = (1.0 - (y - y_min) / (y_max - y_min)) * pixel_height as f64;
+            (x.is_finite() && y.is_finite() && x >= 0.0 && y >= 0.0).then(|| (x.round() as Cell, y.round() as Cell))
+        };
+
+        for dataset in &self.datasets {
+            let points: Vec<_> = dataset.points.iter().map(|point| scale(*point)).collect();
+
+            for (i, point) in points.iter().enumerate() {
+                match (point, points.get(i + 1)) {
+                    (Some(start), Some(Some(end))) if !dataset.scatter =>
+                        pixels.line(*start, *end, dataset.color.clone()),
+                    (Some((x, y)), _) => pixels.point(*x, *y, dataset.color.clone()),
+                    (None, _) => {}
+                }
+            }
+        }
+
+        pixels.blit(canvas, plot_col, row);
+
+        // legend in the top right corner
+        for (i, dataset) in self.datasets.iter().filter(|dataset| !dataset.name.is_empty()).enumerate() {
+            let name = truncate(&dataset.name, plot_width);
+            let name_col = (plot_col + plot_width).saturating_sub(display_width(&name) as Cell);
+            if (i as Cell) < plot_height {
+                canvas.addstr(name_col, row + i as Cell, &name, Some(&Style::new().fg(dataset.color.clone())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(canvas: &InstructionBuffer, row: Cell) -> Str {
+        let (width, _) = canvas.size();
+        canvas.rasterize()[row as usize * width as usize..][..width as usize].iter()
+            .map(|cell| cell.as_ref().map_or(" ", |(grapheme, _)| grapheme.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(format_value(1.5), "1.5");
+        assert_eq!(format_value(2.0), "2");
+        assert_eq!(format_value(0.126), "0.13");
+
+        assert_eq!(eighths(5.0, 10.0, 2), 8);
+        assert_eq!(eighths(20.0, 10.0, 2), 16);
+        assert_eq!(eighths(-1.0, 10.0, 2), 0);
+        assert_eq!(eighths(f64::NAN, 10.0, 2), 0);
+        assert_eq!(eighths(1.0, 0.0, 2), 0);
+    }
+
+    #[test]
+    fn sparkline_shows_the_last_values() {
+        let data = [8.0, 0.0, 1.0, 4.0, 8.0];
+        let mut canvas = InstructionBuffer::new(4, 1, None);
+        Sparkline::new(&data).render(&mut canvas, 0, 0, 4, 1);
+        assert_eq!(symbols(&canvas, 0), " ▁▄█");
+    }
+
+    #[test]
+    fn horizontal_bars() {
+        let bars = vec![("漢".to_string(), 2.0), ("b".to_string(), 1.0)];
+        let mut canvas = InstructionBuffer::new(12, 2, None);
+        BarChart::horizontal(bars).render(&mut canvas, 0, 0, 12, 2);
+
+        // wide labels take up two columns
+        assert_eq!(symbols(&canvas, 0), "漢 ███████ 2");
+        assert_eq!(symbols(&canvas, 1), "b  ███▌ 1   ");
+    }
+
+    #[test]
+    fn vertical_bars() {
+        let bars = vec![("a".to_string(), 1.0), ("bb".to_string(), 2.0)];
+        let mut canvas = InstructionBuffer::new(5, 3, None);
+        BarChart::vertical(bars).render(&mut canvas, 0, 0, 5, 3);
+
+        assert_eq!(symbols(&canvas, 0), "   ██");
+        assert_eq!(symbols(&canvas, 1), "██ ██");
+        assert_eq!(symbols(&canvas, 2), "a  bb");
+    }
+
+    #[test]
+    fn line_chart_bounds() {
+        let chart = LineChart::new(vec![Dataset::new(vec![(0.0, 3.0), (2.0, f64::NAN), (4.0, -1.0)])]);
+        assert_eq!(chart.bounds(|point| point.0), (0.0, 4.0));
+        assert_eq!(chart.bounds(|point| point.1), (-1.0, 3.0));
+
+        let chart = LineChart::new(vec![Dataset::new(vec![(1.0, 1.0)])]);
+        assert_eq!(chart.bounds(|point| point.0), (0.0, 2.0));
+        assert_eq!(LineChart::new(Vec::new()).bounds(|point| point.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn single_row_line_chart_keeps_the_max_label() {
+        let chart = LineChart::new(vec![Dataset::new(vec![(0.0, 0.0), (1.0, 5.0)])]).y_bounds(0.0, 10.0);
+        let mut canvas = InstructionBuffer::new(8, 3, None);
+        chart.render(&mut canvas, 0, 0, 8, 3);
+
+        assert!(symbols(&canvas, 0).starts_with("10│"));
+        assert_eq!(symbols(&canvas, 1), "  └─────");
+        assert_eq!(symbols(&canvas, 2), "   0   1");
+    }
+}
@@ -0,0 +1,218 @@
+use crate::*;
+
+/// Width and height of the shadow cast to the right of and below a layer
+const SHADOW_OFFSET: (Cell, Cell) = (2, 1);
+
+/// A canvas placed on the screen, above or below other layers
+pub struct Layer<'a> {
+    pub canvas: InstructionBuffer<'a>,
+    pub col: Cell,
+    pub row: Cell,
+    /// Layers with a higher z-index are drawn on top, and equal ones in the order they were added
+    pub z: i16,
+    /// Given to whatever is below the shadow cast by the layer
+    pub shadow: Option<Style>,
+}
+
+impl<'a> Layer<'a> {
+    pub const SHADOW: Style = Style::new().fg(Color::BrightBlack).bg(Color::Black);
+
+    pub fn new(canvas: InstructionBuffer<'a>, col: Cell, row: Cell) -> Self {
+        Self {
+            canvas,
+            col,
+            row,
+            z: 0,
+            shadow: None,
+        }
+    }
+
+    pub fn z(mut self, z: i16) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn shadow(mut self) -> Self {
+        self.shadow = Some(Self::SHADOW);
+        self
+    }
+
+    pub fn shadow_style(mut self, style: Style) -> Self {
+        self.shadow = Some(style);
+        self
+    }
+}
+
+/// Combines layers into a single frame. Every layer covers its whole area, including cells
+/// that were never drawn to, so nothing below it bleeds through.
+pub struct Compositor<'a> {
+    pub layers: Vec<Layer<'a>>,
+    width: Cell,
+    height: Cell,
+}
+
+impl<'a> Compositor<'a> {
+    pub fn new(width: Cell, height: Cell) -> Self {
+        Self {
+            layers: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    /// Add a layer, returning its index in `Compositor::layers`
+    pub fn add(&mut self, layer: Layer<'a>) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Remove every layer
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+
+    /// The grapheme and style of every cell in the frame, row by row
+    fn composite(&self) -> Vec<Option<(Str, Style)>> {
+        let mut frame = vec![None; self.width as usize * self.height as usize];
+        let mut layers: Vec<_> = self.layers.iter().collect();
+        layers.sort_by_key(|layer| layer.z);
+
+        for layer in layers {
+            let (width, height) = (layer.canvas.width, layer.canvas.height);
+
+            if let Some(style) = &layer.shadow && width > 0 && height > 0 {
+                let (offset_col, offset_row) = SHADOW_OFFSET;
+                let right = (layer.col + width, layer.row + offset_row, offset_col, height);
+                let below = (layer.col + offset_col, layer.row + height, width, offset_row);
+
+                for (col, row, width, height) in [right, below] {
+                    for row in row..row.saturating_add(height) {
+                        for col in col..col.saturating_add(width) {
+                            if let Some(i) = self.index(col, row) {
+                                let cell = frame[i].get_or_insert_with(|| (" ".to_string(), Style::new()));
+                                cell.1 = style.clone();
+                            }
+                        }
+                    }
+                }
+            }
+
+            let cells = layer.canvas.rasterize();
+            for row in 0..height {
+                for col in 0..width {
+                    if let Some(i) = self.index(layer.col.saturating_add(col), layer.row.saturating_add(row)) {
+                        frame[i] = Some(cells[row as usize * width as usize + col as usize].clone()
+                            .unwrap_or_else(|| (" ".to_string(), layer.canvas.default_style.clone())));
+                    }
+                }
+            }
+        }
+
+        for row in frame.chunks_mut(self.width.max(1).into()) {
+            repair_wide(row);
+        }
+
+        frame
+    }
+
+    fn index(&self, col: Cell, row: Cell) -> Option<usize> {
+        (col < self.width && row < self.height)
+            .then(|| row as usize * self.width as usize + col as usize)
+    }
+
+    /// Render the whole frame, with empty cells where there is no layer
+    pub fn render(&self, start_col: Cell, start_row: Cell) -> Str {
+        let frame = self.composite();
+        let empty = (" ".to_string(), Style::new());
+        let mut result = String::new();
+
+        for (row, cells) in frame.chunks(self.width.max(1).into()).enumerate() {
+            result.push_str(&formatf!("{CUR_SET}", start_row + row as Cell + 1, start_col + 1));
+
+            let mut current = None;
+            for (grapheme, style) in cells.iter().map(|cell| cell.as_ref().unwrap_or(&empty)) {
+                if current != Some(style) {
+                    result.push_str(&style.as_string());
+                    current = Some(style);
+                }
+                result.push_str(grapheme);
+            }
+        }
+
+        result
+    }
+}
+
+/// Replace the halves of wide graphemes that were partly covered by another layer with spaces,
+/// so that every cell still takes up exactly one column
+fn repair_wide(row: &mut [Option<(Str, Style)>]) {
+    let mut covered = 0;
+
+    for col in 0..row.len() {
+        let Some((grapheme, _)) = &row[col] else {
+            covered = 0;
+            continue
+        };
+
+        if grapheme.is_empty() {
+            if covered > 0 {
+                covered -= 1;
+            } else if let Some((grapheme, _)) = &mut row[col] {
+                *grapheme = " ".to_string();
+            }
+            continue
+        }
+
+        let width = utils::grapheme_width(grapheme);
+        let end = col + width;
+        let whole = end <= row.len() && row[col + 1..end].iter()
+            .all(|cell| cell.as_ref().is_some_and(|(grapheme, _)| grapheme.is_empty()));
+
+        if whole {
+            covered = width - 1;
+        } else if let Some((grapheme, _)) = &mut row[col] {
+            *grapheme = " ".to_string();
+            covered = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(compositor: &Compositor) -> Vec<Str> {
+        compositor.composite().into_iter()
+            .map(|cell| cell.map_or("?".to_string(), |(grapheme, _)| grapheme))
+            .collect()
+    }
+
+    fn layer(width: Cell, col: Cell, text: &str) -> Layer<'static> {
+        let mut canvas = InstructionBuffer::new(width, 1, None);
+        canvas.addstr(0, 0, text, None);
+        Layer::new(canvas, col, 0)
+    }
+
+    #[test]
+    fn wide_graphemes_take_two_cells() {
+        let mut compositor = Compositor::new(6, 1);
+        compositor.add(layer(6, 0, "漢a字"));
+        assert_eq!(symbols(&compositor), ["漢", "", "a", "字", "", " "]);
+
+        // cut off at the right edge of the layer
+        let mut compositor = Compositor::new(3, 1);
+        compositor.add(layer(3, 0, "a漢字"));
+        assert_eq!(symbols(&compositor), ["a", "漢", ""]);
+        let mut compositor = Compositor::new(2, 1);
+        compositor.add(layer(2, 0, "a漢"));
+        assert_eq!(symbols(&compositor), ["a", " "]);
+    }
+
+    #[test]
+    fn partly_covered_wide_graphemes_become_spaces() {
+        let mut compositor = Compositor::new(6, 1);
+        compositor.add(layer(6, 0, "漢字漢"));
+        compositor.add(layer(2, 1, "ab"));
+        assert_eq!(symbols(&compositor), [" ", "a", "b", " ", "漢", ""]);
+    }
+}
@@ -24,6 +24,9 @@ pub const CUR_COL_HOME: &'static str = "\x1b[0G";
 pub const CUR_HIDE: &'static str = "\x1b[?25l";
 pub const CUR_SHOW: &'static str = "\x1b[?25h";
 
+/// The terminal replies with `\x1b[{row};{col}R`, see `utils::cursor_position`
+pub const CUR_QUERY: &'static str = "\x1b[6n";
+
 pub const CUR_SAVE: &'static str = "\x1b7";
 pub const CUR_RESTORE: &'static str = "\x1b8";
 pub const CUR_SAVE_SCO: &'static str = "\x1b[s";
@@ -34,11 +37,27 @@ pub const CUR_RESTORE_SCO: &'static str = "\x1b[u";
 pub const SCREEN_SAVE: &'static str = "\x1b[?47h";
 pub const RESTORE_SAVE: &'static str = "\x1b[?47l";
 
+/// Switch to the alternate screen, saving the cursor, so that the main screen and its
+/// scrollback are left as they are. Preferred over `SCREEN_SAVE`.
+pub const ALT_SCREEN_ENTER: &'static str = "\x1b[?1049h";
+/// Switch back to the main screen, restoring the cursor
+pub const ALT_SCREEN_LEAVE: &'static str = "\x1b[?1049l";
+
 pub const ERASE_SCREEN: &'static str = "\x1b[2J";
 pub const ERASE_LINE: &'static str = "\x1b[2K";
 pub const ERASE_TO_END: &'static str = "\x1b[0J";
 pub const ERASE_TO_LINE_END: &'static str = "\x1b[0J";
 
+// Input modes
+
+/// Report mouse presses, releases and drags, in the format read by `Event::parse`
+pub const MOUSE_ENABLE: &'static str = "\x1b[?1000h\x1b[?1002h\x1b[?1006h";
+pub const MOUSE_DISABLE: &'static str = "\x1b[?1006l\x1b[?1002l\x1b[?1000l";
+
+/// Surround pasted text with `PASTE_START` and `PASTE_END`
+pub const PASTE_ENABLE: &'static str = "\x1b[?2004h";
+pub const PASTE_DISABLE: &'static str = "\x1b[?2004l";
+
 // Style
 
 pub const RESET: &'static str = "\x1b[0m";
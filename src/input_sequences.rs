@@ -19,6 +19,13 @@ pub const PG_DOWN: &[u8] = b"\x1b[6~";
 pub const DELETE: &[u8] = b"\x1b[3~";
 pub const INSERT: &[u8] = b"\x1b[2~";
 
+/// Surround pasted text while bracketed paste is enabled
+pub const PASTE_START: &[u8] = b"\x1b[200~";
+pub const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Starts mouse events reported in the SGR format, like `\x1b[<0;10;5M`
+pub const MOUSE_SGR: &[u8] = b"\x1b[<";
+
 // Arrows
 
 pub const ARROW_UP: &[u8] = b"\x1b[A";
@@ -3,12 +3,46 @@ pub mod control_sequences;
 pub mod input_sequences;
 pub mod utils;
 mod macros;
+mod paner;
+mod box_drawing;
+mod compositor;
+mod text;
+mod pixels;
+mod charts;
+mod widget;
+mod text_input;
+mod text_area;
+mod list;
+mod table;
+mod tree;
+mod progress;
+mod viewport;
+#[cfg(feature = "crossterm")]
+mod terminal;
+
+pub use paner::*;
+pub use compositor::*;
+pub use text::*;
+pub use pixels::*;
+pub use charts::*;
+pub use widget::*;
+pub use text_input::*;
+pub use text_area::*;
+pub use list::*;
+pub use table::*;
+pub use tree::*;
+pub use progress::*;
+pub use viewport::*;
+#[cfg(feature = "crossterm")]
+pub use terminal::*;
 
 use unicode_segmentation::UnicodeSegmentation;
 use control_sequences::*;
 
 use std::fmt;
+use std::str::FromStr;
 use std::collections::HashMap;
+use std::cell::RefCell;
 
 type Cell = u16;
 type Str = String;
@@ -24,6 +58,17 @@ pub trait Canvas {
     fn setstyle(&mut self, style: Option<&Style>);
     fn setcursor(&mut self, col: Cell, row: Cell);
 
+    /// The width and height that filled areas are clipped to, unbounded by default
+    fn size(&self) -> (Cell, Cell) {
+        (Cell::MAX, Cell::MAX)
+    }
+
+    /// The grapheme last drawn in a cell, if the canvas keeps track of it.
+    /// Used by `Canvas::draw_box` to join lines with the ones below them.
+    fn symbol_at(&self, _col: Cell, _row: Cell) -> Option<Str> {
+        None
+    }
+
     fn addstr(&mut self, col: Cell, row: Cell, string: &str, style: Option<&Style>) {
         self.setcursor(col, row);
         self.setstyle(style);
@@ -45,12 +90,36 @@ pub trait Canvas {
     fn draw_box(&mut self, col: Cell, row: Cell, width: Cell, height: Cell,
             border: &BorderStyle, style: Option<&Style>) {
 
-        let (h, v, tl, tr, bl, br) = match *border {
-            BorderStyle::Gap(..) => ("", "", "", "", "", ""),
-            BorderStyle::Connected([h, v, tl, tr, bl, br, _, _, _, _, _]) |
-            BorderStyle::Disconnected([h, v, tl, tr, bl, br]) => (h, v, tl, tr, bl, br),
+        let Some([h, v, tl, tr, bl, br]) = border.box_chars() else {
+            return
+        };
+
+        let right = col + width.saturating_sub(1);
+        let bottom = row + height.saturating_sub(1);
+
+        // find where lines already drawn cross the box, before drawing over them
+        let mut junctions = Vec::new();
+        let mut join = |canvas: &Self, col, row, ch| {
+            if let Some(below) = canvas.symbol_at(col, row) {
+                let joined = box_drawing::merge(&below, ch);
+                if joined != ch {
+                    junctions.push((col, row, joined));
+                }
+            }
         };
 
+        for i in col + 1..right {
+            join(self, i, row, h);
+            join(self, i, bottom, h);
+        }
+        for i in row + 1..bottom {
+            join(self, col, i, v);
+            join(self, right, i, v);
+        }
+        for (col, row, ch) in [(col, row, tl), (right, row, tr), (col, bottom, bl), (right, bottom, br)] {
+            join(self, col, row, ch);
+        }
+
         // draw sides
         self.draw_hbar(col + 1, row, width.saturating_sub(2), h, style);
         self.draw_hbar(col + 1, row + height.saturating_sub(1), width.saturating_sub(2), h, style);
@@ -58,21 +127,309 @@ pub trait Canvas {
         self.draw_vbar(col + width.saturating_sub(1), row + 1, height.saturating_sub(2), v, style);
 
         draw_corners(self, col, row, width, height, tl, tr, bl, br, style);
+
+        for (col, row, ch) in junctions {
+            self.setcursor(col, row);
+            self.addtext(ch);
+        }
+    }
+
+    /// Draw a straight line between two cells, clipped to the size of the canvas
+    fn draw_line(&mut self, start: (Cell, Cell), end: (Cell, Cell), ch: &str, style: Option<&Style>) {
+        let (width, height) = self.size();
+        let (mut col, mut row) = (start.0 as i32, start.1 as i32);
+        let (end_col, end_row) = (end.0 as i32, end.1 as i32);
+
+        let delta_col = (end_col - col).abs();
+        let delta_row = -(end_row - row).abs();
+        let step_col = if col < end_col { 1 } else { -1 };
+        let step_row = if row < end_row { 1 } else { -1 };
+        let mut error = delta_col + delta_row;
+
+        self.setstyle(style);
+        loop {
+            if col < width as i32 && row < height as i32 {
+                self.setcursor(col as Cell, row as Cell);
+                self.addtext(ch);
+            }
+
+            if col == end_col && row == end_row {
+                break
+            }
+
+            let double = error * 2;
+            if double >= delta_row {
+                error += delta_row;
+                col += step_col;
+            }
+            if double <= delta_col {
+                error += delta_col;
+                row += step_row;
+            }
+        }
+    }
+
+    /// Fill an area with a character, clipped to the size of the canvas
+    fn fill_rect(&mut self, col: Cell, row: Cell, width: Cell, height: Cell,
+            ch: &str, style: Option<&Style>) {
+
+        let (max_width, max_height) = self.size();
+        let width = width.min(max_width.saturating_sub(col));
+        let height = height.min(max_height.saturating_sub(row));
+
+        for i in 0..height {
+            self.draw_hbar(col, row + i, width, ch, style);
+        }
+    }
+
+    /// Fill an area with spaces in the default style
+    fn clear_rect(&mut self, col: Cell, row: Cell, width: Cell, height: Cell) {
+        self.fill_rect(col, row, width, height, " ", None);
+    }
+
+    /// Fill an area with a background going from one color to another, in the direction of
+    /// `towards`
+    #[allow(clippy::too_many_arguments)]
+    fn fill_gradient(&mut self, col: Cell, row: Cell, width: Cell, height: Cell,
+            from: (u8, u8, u8), to: (u8, u8, u8), towards: Direction) {
+
+        let (from, to) = match towards {
+            Direction::Right | Direction::Down => (from, to),
+            Direction::Left | Direction::Up => (to, from),
+        };
+
+        let steps = match towards {
+            Direction::Left | Direction::Right => width,
+            Direction::Up | Direction::Down => height,
+        };
+
+        for i in 0..steps {
+            let style = Style::new().bg(blend(from, to, i, steps));
+            match towards {
+                Direction::Left | Direction::Right => self.fill_rect(col + i, row, 1, height, " ", Some(&style)),
+                Direction::Up | Direction::Down => self.fill_rect(col, row + i, width, 1, " ", Some(&style)),
+            }
+        }
+    }
+
+    /// Draw word wrapped text within an area, returning the number of rows used.
+    /// Lines are also broken on `\n`, and the last row ends with an ellipsis if the text
+    /// does not fit.
+    #[allow(clippy::too_many_arguments)]
+    fn addparagraph(&mut self, col: Cell, row: Cell, width: Cell, height: Cell,
+            text: &str, align: Align, style: Option<&Style>) -> Cell {
+
+        if width == 0 || height == 0 || text.is_empty() {
+            return 0
+        }
+
+        let lines = wrap(text, width.into());
+        let rows = lines.len().min(height.into());
+        let overflows = rows < lines.len();
+
+        for (i, (words, end)) in lines.iter().take(rows).enumerate() {
+            let last = i + 1 == rows && overflows;
+
+            let line = if last {
+                utils::truncate(&(words.join(" ") + "…"), width)
+            } else if align == Align::Justify && !end && words.len() > 1 {
+                justify(words, width.into())
+            } else {
+                words.join(" ")
+            };
+
+            let space = width.saturating_sub(utils::display_width(&line) as Cell);
+            let offset = match align {
+                Align::Left | Align::Justify => 0,
+                Align::Center => space / 2,
+                Align::Right => space,
+            };
+
+            self.addstr(col + offset, row + i as Cell, &line, style);
+        }
+
+        rows as Cell
+    }
+
+    /// Draw a line of spans, ending with an ellipsis if it is wider than the width
+    fn addline(&mut self, col: Cell, row: Cell, width: Cell, line: &Line) {
+        if width == 0 {
+            return
+        }
+
+        let len = line.width();
+        let overflows = len > width;
+        let offset = match line.align {
+            _ if overflows => 0,
+            Align::Left | Align::Justify => 0,
+            Align::Center => (width - len) / 2,
+            Align::Right => width - len,
+        };
+
+        let mut left = if overflows { width - 1 } else { width };
+        self.setcursor(col + offset, row);
+
+        for span in &line.spans {
+            let mut text = Str::new();
+            for grapheme in span.content.graphemes(true) {
+                let width = utils::grapheme_width(grapheme) as Cell;
+                if width > left {
+                    left = 0;
+                    break
+                }
+                text.push_str(grapheme);
+                left -= width;
+            }
+
+            self.setstyle(span.style.as_ref());
+            self.addtext(&text);
+
+            if left == 0 {
+                if overflows {
+                    self.addtext("…");
+                }
+                break
+            }
+        }
+    }
+
+    /// Draw lines of spans below each other, returning the number of rows used
+    fn addlines(&mut self, col: Cell, row: Cell, width: Cell, height: Cell, text: &Text) -> Cell {
+        let rows = text.lines.len().min(height.into());
+
+        for (i, line) in text.lines.iter().take(rows).enumerate() {
+            self.addline(col, row + i as Cell, width, line);
+        }
+
+        rows as Cell
+    }
+}
+
+/// The color a step of the way between two colors
+fn blend(from: (u8, u8, u8), to: (u8, u8, u8), step: Cell, steps: Cell) -> Color {
+    let channel = |from: u8, to: u8| {
+        let distance = steps.saturating_sub(1).max(1) as i32;
+        (from as i32 + (to as i32 - from as i32) * step as i32 / distance) as u8
+    };
+
+    Color::True(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
+
+/// Characters covering a cell to some degree
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shade {
+    Light,
+    Medium,
+    Dark,
+    Full,
+}
+
+impl Shade {
+    pub const fn as_str(self) -> Static {
+        match self {
+            Self::Light => "░",
+            Self::Medium => "▒",
+            Self::Dark => "▓",
+            Self::Full => "█",
+        }
+    }
+
+    /// The shade closest to a fraction between 0 and 1, or `None` for an empty cell
+    pub fn from_fraction(fraction: f32) -> Option<Self> {
+        match (fraction.clamp(0.0, 1.0) * 4.0).round() as u8 {
+            0 => None,
+            1 => Some(Self::Light),
+            2 => Some(Self::Medium),
+            3 => Some(Self::Dark),
+            _ => Some(Self::Full),
+        }
     }
 }
 
-// NOTE these are also used in Paner, hence the ugly separation from Canvas.draw_box
+/// The words of every line when wrapping text, and whether the line ends a paragraph.
+/// Words wider than the width are broken.
+fn wrap(text: &str, width: usize) -> Vec<(Vec<&str>, bool)> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split('\n') {
+        let mut line = Vec::new();
+        let mut len = 0;
+
+        for mut word in paragraph.split_whitespace() {
+            loop {
+                let word_len = utils::display_width(word);
+                let needed = if line.is_empty() { word_len } else { len + 1 + word_len };
+
+                if needed <= width {
+                    line.push(word);
+                    len = needed;
+                    break
+                }
+
+                if !line.is_empty() {
+                    lines.push((std::mem::take(&mut line), false));
+                    len = 0;
+                    continue
+                }
+
+                // break after as many columns as fit, but after at least one grapheme
+                let mut used = 0;
+                let split = word.grapheme_indices(true)
+                    .find(|&(i, grapheme)| {
+                        used += utils::grapheme_width(grapheme);
+                        i > 0 && used > width
+                    })
+                    .map_or(word.len(), |(i, _)| i);
+
+                if split == word.len() {
+                    // a single grapheme wider than the width
+                    line.push(word);
+                    len = word_len;
+                    break
+                }
+
+                lines.push((vec![&word[..split]], false));
+                word = &word[split..];
+            }
+        }
+
+        lines.push((line, true));
+    }
 
+    lines
+}
+
+/// Join words with spaces so that they fill the width, with wider gaps to the left
+fn justify(words: &[&str], width: usize) -> Str {
+    let len: usize = words.iter().map(|word| utils::display_width(word)).sum();
+    let gaps = words.len() - 1;
+    let spaces = width.saturating_sub(len).max(gaps);
+
+    let mut line = Str::new();
+    for (i, word) in words.iter().enumerate() {
+        if i > 0 {
+            let extra = (i <= spaces % gaps) as usize;
+            line.push_str(&" ".repeat(spaces / gaps + extra));
+        }
+        line.push_str(word);
+    }
+
+    line
+}
+
+// NOTE kept apart from Canvas.draw_box, so that corners can be drawn without the sides
+
+#[allow(clippy::too_many_arguments)]
 fn draw_corners(
         canvas: &mut (impl Canvas + ?Sized),
         col: Cell,
         row: Cell,
         width: Cell,
         height: Cell,
-        tl: Static,
-        tr: Static,
-        bl: Static,
-        br: Static,
+        tl: &str,
+        tr: &str,
+        bl: &str,
+        br: &str,
         style: Option<&Style>) {
 
     canvas.setstyle(style);
@@ -86,11 +443,16 @@ fn draw_corners(
     canvas.addtext(br);
 }
 
+#[derive(Clone)]
 pub enum BorderStyle {
     /// An empty gap of some size
     Gap(Cell),
     Connected([Static; 11]),
     Disconnected([Static; 6]),
+    /// Like `Connected`, with characters that are not static, such as ones parsed from text
+    ConnectedOwned(Box<[Str; 11]>),
+    /// Like `Disconnected`, with characters that are not static
+    DisconnectedOwned(Box<[Str; 6]>),
 }
 
 impl BorderStyle {
@@ -108,7 +470,37 @@ impl BorderStyle {
         BorderStyle::Connected(["═", "║", "╔", "╗", "╚", "╝", "╠", "╣", "╦", "╩", "╬"]);
     pub const DISCONNECTED_DOUBLE: Self =
         BorderStyle::Disconnected(["═", "║", "╔", "╗", "╚", "╝"]);
-    
+
+    pub const CONNECTED_ROUNDED: Self =
+        BorderStyle::Connected(["─", "│", "╭", "╮", "╰", "╯", "├", "┤", "┬", "┴", "┼"]);
+    pub const DISCONNECTED_ROUNDED: Self =
+        BorderStyle::Disconnected(["─", "│", "╭", "╮", "╰", "╯"]);
+
+    pub const CONNECTED_DASHED: Self =
+        BorderStyle::Connected(["╌", "╎", "┌", "┐", "└", "┘", "├", "┤", "┬", "┴", "┼"]);
+    pub const DISCONNECTED_DASHED: Self =
+        BorderStyle::Disconnected(["╌", "╎", "┌", "┐", "└", "┘"]);
+
+    pub const CONNECTED_HEAVY_DASHED: Self =
+        BorderStyle::Connected(["╍", "╏", "┏", "┓", "┗", "┛", "┣", "┫", "┳", "┻", "╋"]);
+    pub const DISCONNECTED_HEAVY_DASHED: Self =
+        BorderStyle::Disconnected(["╍", "╏", "┏", "┓", "┗", "┛"]);
+
+    /// Names used for the constants when written as text
+    const NAMED: [(Static, Self); 12] = [
+        ("connected-light", Self::CONNECTED_LIGHT),
+        ("disconnected-light", Self::DISCONNECTED_LIGHT),
+        ("connected-heavy", Self::CONNECTED_HEAVY),
+        ("disconnected-heavy", Self::DISCONNECTED_HEAVY),
+        ("connected-double", Self::CONNECTED_DOUBLE),
+        ("disconnected-double", Self::DISCONNECTED_DOUBLE),
+        ("connected-rounded", Self::CONNECTED_ROUNDED),
+        ("disconnected-rounded", Self::DISCONNECTED_ROUNDED),
+        ("connected-dashed", Self::CONNECTED_DASHED),
+        ("disconnected-dashed", Self::DISCONNECTED_DASHED),
+        ("connected-heavy-dashed", Self::CONNECTED_HEAVY_DASHED),
+        ("disconnected-heavy-dashed", Self::DISCONNECTED_HEAVY_DASHED),
+    ];
 
     pub fn connected_from_str(string: Static) -> Self {
         let vec: Vec<_> = string.graphemes(true).collect();
@@ -117,19 +509,133 @@ impl BorderStyle {
 
     pub fn disconnected_from_str(string: Static) -> Self {
         let vec: Vec<_> = string.graphemes(true).collect();
-        Self::Connected(vec.try_into().expect("expected 6 characters"))
+        Self::Disconnected(vec.try_into().expect("expected 6 characters"))
     }
 
     fn gap(&self) -> Cell {
         match self {
             Self::Gap(cells) => *cells,
-            Self::Connected(..) => 1,
-            Self::Disconnected(..) => 2,
+            Self::Connected(..) | Self::ConnectedOwned(..) => 1,
+            Self::Disconnected(..) | Self::DisconnectedOwned(..) => 2,
+        }
+    }
+
+    /// The characters of a connected style, whether static or owned
+    pub(crate) fn connected_chars(&self) -> Option<[&str; 11]> {
+        match self {
+            Self::Connected(chars) => Some(*chars),
+            Self::ConnectedOwned(chars) => Some(chars.each_ref().map(Str::as_str)),
+            _ => None,
+        }
+    }
+
+    /// The horizontal and vertical lines and the corners of a connected or disconnected style
+    pub(crate) fn box_chars(&self) -> Option<[&str; 6]> {
+        match self {
+            Self::Gap(..) => None,
+            Self::Disconnected(chars) => Some(*chars),
+            Self::DisconnectedOwned(chars) => Some(chars.each_ref().map(Str::as_str)),
+            _ => self.connected_chars().map(|[h, v, tl, tr, bl, br, ..]| [h, v, tl, tr, bl, br]),
+        }
+    }
+}
+
+/// Styles with the same characters are equal, whether the characters are static or owned
+impl PartialEq for BorderStyle {
+    fn eq(&self, other: &Self) -> bool {
+        self.gap() == other.gap()
+            && self.connected_chars() == other.connected_chars()
+            && self.box_chars() == other.box_chars()
+    }
+}
+
+/// Written as the name of a constant in kebab case, such as `connected-light`, or as its kind
+/// followed by its size or characters, such as `gap:1` and `disconnected:-|++++`
+impl fmt::Display for BorderStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if let Some((name, _)) = Self::NAMED.iter().find(|(_, style)| style == self) {
+            return write!(f, "{name}")
+        }
+
+        match self {
+            Self::Gap(cells) => write!(f, "gap:{cells}"),
+            Self::Connected(chars) => write!(f, "connected:{}", chars.concat()),
+            Self::Disconnected(chars) => write!(f, "disconnected:{}", chars.concat()),
+            Self::ConnectedOwned(chars) => write!(f, "connected:{}", chars.concat()),
+            Self::DisconnectedOwned(chars) => write!(f, "disconnected:{}", chars.concat()),
+        }
+    }
+}
+
+impl FromStr for BorderStyle {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        if let Some((_, style)) = Self::NAMED.iter().find(|(name, _)| *name == string) {
+            return Ok(style.clone())
+        }
+
+        let Some((kind, rest)) = string.split_once(':') else {
+            return Err(ParseError { position: 0, message: "unknown border style" })
+        };
+
+        let position = kind.len() + 1;
+        let chars = || rest.graphemes(true).map(str::to_string).collect::<Vec<_>>();
+
+        match kind {
+            "gap" => rest.parse().map(Self::Gap)
+                .map_err(|_| ParseError { position, message: "expected a size" }),
+            "connected" => chars().try_into().map(|chars| Self::ConnectedOwned(Box::new(chars)))
+                .map_err(|_| ParseError { position, message: "expected 11 characters" }),
+            "disconnected" => chars().try_into().map(|chars| Self::DisconnectedOwned(Box::new(chars)))
+                .map_err(|_| ParseError { position, message: "expected 6 characters" }),
+            _ => Err(ParseError { position: 0, message: "unknown border style" }),
         }
     }
 }
 
-#[derive(Default, Clone)]
+#[cfg(feature = "serde")]
+impl serde::Serialize for BorderStyle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BorderStyle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Str::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// An error from parsing text, with the byte position it occured at
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: Static,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches lines to fill the width, except the last line of every paragraph
+    Justify,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Default, Clone, PartialEq)]
 pub enum Color {
     #[default]
     Default,
@@ -213,7 +719,78 @@ impl Color {
     }
 }
 
-#[derive(Clone)]
+/// Parsed from a name in snake case such as `bright_red`, an ANSI color id such as `208`,
+/// or a hex code such as `#ff8000`
+impl FromStr for Color {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        let color = match string {
+            "default" => Color::Default,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "white" => Color::White,
+            "bright_black" => Color::BrightBlack,
+            "bright_red" => Color::BrightRed,
+            "bright_green" => Color::BrightGreen,
+            "bright_yellow" => Color::BrightYellow,
+            "bright_blue" => Color::BrightBlue,
+            "bright_magenta" => Color::BrightMagenta,
+            "bright_cyan" => Color::BrightCyan,
+            "bright_white" => Color::BrightWhite,
+            _ => {
+                if let Some(hex) = string.strip_prefix('#') && hex.len() == 6 && hex.is_ascii() {
+                    let channel = |i| u8::from_str_radix(&hex[i..i + 2], 16);
+                    if let (Ok(r), Ok(g), Ok(b)) = (channel(0), channel(2), channel(4)) {
+                        return Ok(Color::True(r, g, b))
+                    }
+                }
+
+                return string.parse().map(Color::Ansi)
+                    .map_err(|_| ParseError { position: 0, message: "unknown color" })
+            }
+        };
+
+        Ok(color)
+    }
+}
+
+/// Written the way it is parsed, using hex codes for true colors
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let name = match self {
+            Color::Default => "default",
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+            Color::White => "white",
+            Color::BrightBlack => "bright_black",
+            Color::BrightRed => "bright_red",
+            Color::BrightGreen => "bright_green",
+            Color::BrightYellow => "bright_yellow",
+            Color::BrightBlue => "bright_blue",
+            Color::BrightMagenta => "bright_magenta",
+            Color::BrightCyan => "bright_cyan",
+            Color::BrightWhite => "bright_white",
+            Color::Ansi(id) => return write!(f, "{id}"),
+            Color::True(r, g, b) => return write!(f, "#{r:02x}{g:02x}{b:02x}"),
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
 pub struct Style {
     pub fg: Option<Color>,
     pub bg: Option<Color>,
@@ -300,6 +877,61 @@ impl Style {
     }
 }
 
+/// Apply the space separated words of a tag, such as `bold red on blue`, to a style.
+/// The start is the position of the tag, used for the positions of errors.
+pub(crate) fn parse_tag(tag: &str, start: usize, mut style: Style) -> Result<Style, ParseError> {
+    let mut words = tag.split(' ')
+        .filter(|word| !word.is_empty())
+        .map(|word| (start + word.as_ptr() as usize - tag.as_ptr() as usize, word));
+
+    while let Some((position, word)) = words.next() {
+        match word {
+            "bold" => style.bold = true,
+            "dim" => style.dim = true,
+            "italic" => style.italic = true,
+            "underline" => style.underline = true,
+            "blink" => style.blink = true,
+            "strike" => style.strike = true,
+            "on" => {
+                let Some((position, word)) = words.next() else {
+                    return Err(ParseError { position, message: "expected a color after `on`" })
+                };
+                style.bg = Some(word.parse().map_err(|err: ParseError|
+                    ParseError { position, ..err })?);
+            }
+            _ => {
+                style.fg = Some(word.parse().map_err(|_|
+                    ParseError { position, message: "unknown style" })?);
+            }
+        }
+    }
+
+    Ok(style)
+}
+
+/// The contents of a tag giving a style when applied to `Style::new()`, the opposite of
+/// `parse_tag`. Colors left as the default are not written.
+pub(crate) fn write_tag(style: &Style) -> Str {
+    let attributes = [
+        (style.bold, "bold"), (style.dim, "dim"), (style.italic, "italic"),
+        (style.underline, "underline"), (style.blink, "blink"), (style.strike, "strike"),
+    ];
+
+    let mut words: Vec<Str> = attributes.iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, word)| word.to_string())
+        .collect();
+
+    if let Some(fg) = &style.fg && *fg != Color::Default {
+        words.push(fg.to_string());
+    }
+    if let Some(bg) = &style.bg && *bg != Color::Default {
+        words.push(format!("on {bg}"));
+    }
+
+    words.join(" ")
+}
+
 impl fmt::Display for Style {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "{}", self.as_string())
@@ -322,6 +954,8 @@ pub struct InstructionBuffer<'a> {
     default_style: &'a Style,
     width: Cell,
     height: Cell,
+    /// The grapheme drawn in every cell, built when first needed by `Canvas::symbol_at`
+    symbols: RefCell<Option<HashMap<(Cell, Cell), Str>>>,
 }
 
 impl<'a> InstructionBuffer<'a> {
@@ -331,6 +965,7 @@ impl<'a> InstructionBuffer<'a> {
             default_style: default_style.unwrap_or_else(|| &Style::EMPTY),
             width,
             height,
+            symbols: RefCell::new(None),
         }
     }
 
@@ -373,15 +1008,98 @@ impl<'a> InstructionBuffer<'a> {
 
         chars
     }
+
+    /// The grapheme and style of every cell written to, row by row.
+    /// A wide grapheme is followed by empty strings for the other cells it covers.
+    /// Commands can not be placed in a cell, so they are skipped.
+    pub(crate) fn rasterize(&self) -> Vec<Option<(Str, Style)>> {
+        let mut cells = vec![None; self.width as usize * self.height as usize];
+        let mut style = self.default_style.clone();
+        let mut col: Cell = 0;
+        let mut row = 0;
+
+        for instruction in self.instructions.iter() {
+            match instruction {
+                Instruction::Text(string) => {
+                    for grapheme in string.graphemes(true) {
+                        let width = utils::grapheme_width(grapheme) as Cell;
+                        if width == 0 {
+                            continue
+                        }
+
+                        if row < self.height {
+                            let i = row as usize * self.width as usize;
+                            // the cells covered by the right side of a wide grapheme are left empty
+                            for (j, col) in (col..col.saturating_add(width).min(self.width)).enumerate() {
+                                let symbol = match j {
+                                    0 if col + width > self.width => " ",
+                                    0 => grapheme,
+                                    _ => "",
+                                };
+                                cells[i + col as usize] = Some((symbol.to_string(), style.clone()));
+                            }
+                        }
+                        col = col.saturating_add(width);
+                    }
+                }
+                Instruction::SetCursor(new_col, new_row) => {
+                    col = *new_col;
+                    row = *new_row;
+                }
+                Instruction::Style(new_style) => {
+                    style = new_style.clone();
+                }
+                Instruction::Command(_) => {}
+            }
+        }
+
+        cells
+    }
+
+    /// The grapheme last drawn in every cell, with nothing in the other cells covered by
+    /// wide graphemes
+    fn symbols(&self) -> HashMap<(Cell, Cell), Str> {
+        let mut symbols = HashMap::new();
+        let mut col: Cell = 0;
+        let mut row = 0;
+
+        for instruction in self.instructions.iter() {
+            match instruction {
+                Instruction::Text(string) => {
+                    for grapheme in string.graphemes(true) {
+                        let width = utils::grapheme_width(grapheme) as Cell;
+                        if width == 0 {
+                            continue
+                        }
+
+                        symbols.insert((col, row), grapheme.to_string());
+                        for col in col.saturating_add(1)..col.saturating_add(width) {
+                            symbols.remove(&(col, row));
+                        }
+                        col = col.saturating_add(width);
+                    }
+                }
+                Instruction::SetCursor(new_col, new_row) => {
+                    col = *new_col;
+                    row = *new_row;
+                }
+                _ => {}
+            }
+        }
+
+        symbols
+    }
 }
 
 impl<'a> Canvas for InstructionBuffer<'a> {
     fn clear(&mut self) {
         self.instructions.clear();
+        self.symbols.take();
     }
 
     fn addtext(&mut self, string: &str) {
         self.instructions.push(Instruction::Text(string.to_string()));
+        self.symbols.take();
     }
 
     fn addcmd(&mut self, cmd: &str) {
@@ -398,6 +1116,17 @@ impl<'a> Canvas for InstructionBuffer<'a> {
         ));
     }
 
+    fn size(&self) -> (Cell, Cell) {
+        (self.width, self.height)
+    }
+
+    fn symbol_at(&self, col: Cell, row: Cell) -> Option<Str> {
+        self.symbols.borrow_mut()
+            .get_or_insert_with(|| self.symbols())
+            .get(&(col, row))
+            .cloned()
+    }
+
     fn render(&self, start_col: Cell, start_row: Cell) -> Str {
         let mut result = String::new();
         let mut row = start_row;
@@ -428,211 +1157,106 @@ impl<'a> Canvas for InstructionBuffer<'a> {
     }
 }
 
-pub enum PaneSize {
-    Fixed(Cell),
-    Relative(Cell),
-}
-
-pub enum Paner<T> {
-    Pane(T),
-    Horizontal(Vec<(PaneSize, Paner<T>)>),
-    Vertical(Vec<(PaneSize, Paner<T>)>),
-}
-
-impl<T> Paner<T> {
-    pub fn render(
-            &self,
-			start_col: Cell,
-			start_row: Cell,
-			width: Cell,
-			height: Cell,
-			border: &BorderStyle) -> (Str, Vec<(&T, Cell, Cell, Cell, Cell)>) {
-
-        let (mut string, arr) = self.render_sub(
-            start_col + 1,
-            start_row + 1,
-            width - 2,
-            height - 2,
-            border,
-            (start_col, start_row, width, height),
-            false
-        );
-
-        let mut canvas = InstructionBuffer::new(width, height, None);
-
-        match border {
-            BorderStyle::Gap(..) => {}
-            BorderStyle::Disconnected(..) => {}
-            BorderStyle::Connected([_, _, tl, tr, bl, br, _, _, _, _, _]) => {
-                // NOTE this is probably very inefficient!
-                let (corners, _) = self.render_sub(
-                    start_col + 1,
-                    start_row + 1,
-                    width - 2,
-                    height - 2,
-                    border,
-                    (start_col, start_row, width, height),
-                    true
-                );
-
-                string.push_str(&corners);
-                draw_corners(&mut canvas, start_col, start_row, width, height, tl, tr, bl, br, None);
-            }
-        }
-
-        string.push_str(&canvas.render(0, 0));
-        (string, arr)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn border_style_from_str() {
+        assert!(BorderStyle::disconnected_from_str("─│┌┐└┘") == BorderStyle::DISCONNECTED_LIGHT);
+        assert!(BorderStyle::connected_from_str("─│┌┐└┘├┤┬┴┼") == BorderStyle::CONNECTED_LIGHT);
+
+        // parsed characters are owned, and equal to the same static ones
+        let parsed: BorderStyle = "disconnected:─│┌┐└┘".parse().unwrap();
+        assert!(matches!(parsed, BorderStyle::DisconnectedOwned(..)));
+        assert!(parsed == BorderStyle::DISCONNECTED_LIGHT && parsed != BorderStyle::CONNECTED_LIGHT);
+        assert_eq!(parsed.to_string(), "disconnected-light");
+        assert_eq!("connected:+-".parse::<BorderStyle>().err().map(|error| error.message), Some("expected 11 characters"));
     }
 
-    fn render_sub(
-            &self,
-			start_col: Cell,
-			start_row: Cell,
-			width: Cell,
-			height: Cell,
-			border: &BorderStyle,
-            original: (Cell, Cell, Cell, Cell),
-            corners: bool) -> (Str, Vec<(&T, Cell, Cell, Cell, Cell)>) {
-
-        let mut arr = Vec::new();
-        let mut string = String::new();
-
-        match self {
-            Self::Pane(pane) => {
-                arr.push((pane, start_col, start_row, width, height));
-
-                match border {
-                    BorderStyle::Disconnected(..) => {
-                        let (width, height) = (width + 2, height + 2);
-                        let mut canvas = InstructionBuffer::new(width, height, None);
-                        canvas.draw_box(0, 0, width, height, border, None);
-                        string.push_str(&canvas.render(start_col - 1, start_row - 1));
-                    }
-                    BorderStyle::Connected(..) |
-                    BorderStyle::Gap(..) => {}
-                }
-            }
-            Self::Horizontal(paners) | Self::Vertical(paners) => {
-                let total_rel: Cell = paners.iter()
-                    .map(|i| if let PaneSize::Relative(size) = i.0 { size } else { 0 }).sum();
-                let total_fixed: Cell = paners.iter()
-                    .map(|i| if let PaneSize::Fixed(size) = i.0 { size } else { 0 }).sum();
-
-                let horizontal = match self {
-                    Self::Horizontal(..) => true,
-                    _ => false
-                };
-
-                let gap = border.gap();
-
-                let mut canvas = InstructionBuffer::new(width + 2, height + 2, None);
+    #[test]
+    fn tags_round_trip() {
+        let style = Style::new().bold().underline().fg(Color::Ansi(208)).bg(Color::True(1, 2, 3));
+        assert!(parse_tag(&write_tag(&style), 0, Style::new()).unwrap() == style);
+        assert_eq!(write_tag(&Style::new()), "");
+    }
 
-                let mut i = 0;
-                for (j, (size, paner)) in paners.iter().enumerate() {
-                    let size = match size {
-                        PaneSize::Relative(size) => {
-                            let size = ((if horizontal { width } else { height })
-                                .saturating_sub(total_fixed) * size / total_rel)
-                                .saturating_sub(gap) - 1;
+    #[test]
+    fn boxes_join_lines_below() {
+        let mut canvas = InstructionBuffer::new(10, 5, None);
+        canvas.draw_box(0, 0, 5, 5, &BorderStyle::CONNECTED_LIGHT, None);
+        assert_eq!(canvas.symbol_at(4, 2).as_deref(), Some("│"));
+
+        canvas.draw_box(4, 0, 5, 5, &BorderStyle::CONNECTED_LIGHT, None);
+        assert_eq!(canvas.symbol_at(4, 0).as_deref(), Some("┬"));
+        assert_eq!(canvas.symbol_at(4, 4).as_deref(), Some("┴"));
+        assert_eq!(canvas.symbol_at(8, 2).as_deref(), Some("│"));
+
+        canvas.addstr(1, 1, "漢x", None);
+        assert_eq!(canvas.symbol_at(1, 1).as_deref(), Some("漢"));
+        assert_eq!(canvas.symbol_at(2, 1), None);
+        assert_eq!(canvas.symbol_at(3, 1).as_deref(), Some("x"));
+    }
 
-                            // extend the last element if it can not be perfect
-                            // TODO improve this and make it even
-                            let size = size + if j == paners.len() - 1 {
-                                (if horizontal { width } else { height }).saturating_sub(size + i)
-                            } else { 0 };
+    #[test]
+    fn wrap_words() {
+        assert_eq!(wrap("one two three", 7), [(vec!["one", "two"], false), (vec!["three"], true)]);
+        assert_eq!(wrap("a\n\nb", 5), [(vec!["a"], true), (vec![], true), (vec!["b"], true)]);
+        assert_eq!(wrap("abcdefg", 3), [(vec!["abc"], false), (vec!["def"], false), (vec!["g"], true)]);
+    }
 
-                            size
-                        }
-                        PaneSize::Fixed(size) => *size
-                    };
+    #[test]
+    fn wrap_wide_words() {
+        assert_eq!(wrap("漢字 漢字", 4), [(vec!["漢字"], false), (vec!["漢字"], true)]);
+        assert_eq!(wrap("漢字漢", 5), [(vec!["漢字"], false), (vec!["漢"], true)]);
+        // a grapheme wider than the width still gets a line of its own
+        assert_eq!(wrap("漢字", 1), [(vec!["漢"], false), (vec!["字"], true)]);
+    }
 
-                    let (new_string, mut new_arr) = paner.render_sub(
-                        if horizontal { start_col + i } else { start_col },
-                        if horizontal { start_row } else { start_row + i },
-                        if horizontal { size } else { width },
-                        if horizontal { height } else { size },
-                        border,
-                        original,
-                        corners
-                    );
-                    string.push_str(&new_string);
-                    arr.append(&mut new_arr);
-
-                    let (old_width, old_height) = (width, height);
-
-                    let (col, row, width, height) = (
-                        if horizontal { i } else { 0 },
-                        if horizontal { 0 } else { i },
-                        if horizontal { size } else { width } + 2,
-                        if horizontal { height } else { size } + 2,
-                    );
-
-                    match border {
-                        BorderStyle::Connected([h, v, _, _, _, _, right, left, down, up, _]) => {
-                            if !corners {
-                                if horizontal {
-                                    canvas.draw_vbar(i, 0, old_height + 1, v, None);
-                                } else {
-                                    canvas.draw_hbar(0, i, old_width + 1, h, None);
-                                }
-
-                                if j == paners.len() - 1 {
-                                    if horizontal {
-                                        canvas.draw_vbar(i + size + 1, 0, old_height + 1, v, None);
-                                    } else {
-                                        canvas.draw_hbar(0, i + size + 1, old_width + 1, h, None);
-                                    }
-                                }
-
-                            } else {
-                                draw_corners(
-                                    &mut canvas,
-                                    col,
-                                    row,
-                                    width,
-                                    height,
-                                    right,
-                                    left,
-                                    up,
-                                    down,
-                                    None
-                                );
-                            }
-                        }
-                        BorderStyle::Disconnected(..) |
-                        BorderStyle::Gap(..) => {}
-                    }
+    #[test]
+    fn fills_are_clipped() {
+        let mut canvas = InstructionBuffer::new(4, 3, None);
+        canvas.fill_rect(2, 1, 5, 5, Shade::Medium.as_str(), None);
+        let cells = canvas.rasterize();
 
-                    if corners {
-                        match border {
-                            BorderStyle::Connected(border) => {
-                                if start_col + col - 1 == original.0 {
-                                    canvas.addstr(col, row, border[6], None);
-                                }
+        let filled: Vec<_> = (0..12).filter(|i| cells[*i].is_some()).collect();
+        assert_eq!(filled, [6, 7, 10, 11]);
+        assert_eq!(cells[11].as_ref().map(|(symbol, _)| symbol.as_str()), Some("▒"));
+    }
 
-                                if start_col + col + width - 1 == original.2 {
-                                    canvas.addstr(col + width - 1, row, border[7], None);
-                                }
+    #[test]
+    fn gradients_reach_both_colors() {
+        let background = |canvas: &InstructionBuffer, i: usize| {
+            canvas.rasterize()[i].clone().and_then(|(_, style)| style.bg)
+        };
 
-                                if start_row + row - 1 == original.1 {
-                                    canvas.addstr(col, row, border[8], None);
-                                }
+        let mut canvas = InstructionBuffer::new(5, 2, None);
+        canvas.fill_gradient(0, 0, 5, 2, (0, 0, 0), (200, 100, 40), Direction::Right);
+        assert!(background(&canvas, 0) == Some(Color::True(0, 0, 0)));
+        assert!(background(&canvas, 7) == Some(Color::True(100, 50, 20)));
+        assert!(background(&canvas, 9) == Some(Color::True(200, 100, 40)));
 
-                                if start_row + row + height - 1 == original.3 {
-                                    canvas.addstr(col, row + height - 1, border[9], None);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
+        let mut canvas = InstructionBuffer::new(1, 3, None);
+        canvas.fill_gradient(0, 0, 1, 3, (0, 0, 0), (10, 10, 10), Direction::Up);
+        assert!(background(&canvas, 0) == Some(Color::True(10, 10, 10)));
+        assert!(background(&canvas, 2) == Some(Color::True(0, 0, 0)));
 
-                    i += size + gap;
-                }
+        assert!(blend((10, 10, 10), (20, 20, 20), 0, 1) == Color::True(10, 10, 10));
+    }
 
-                string.push_str(&canvas.render(start_col - 1, start_row - 1));
-            }
-        }
+    #[test]
+    fn shades_by_fraction() {
+        assert_eq!(Shade::from_fraction(0.1), None);
+        assert_eq!(Shade::from_fraction(0.3), Some(Shade::Light));
+        assert_eq!(Shade::from_fraction(0.5), Some(Shade::Medium));
+        assert_eq!(Shade::from_fraction(0.7), Some(Shade::Dark));
+        assert_eq!(Shade::from_fraction(2.0), Some(Shade::Full));
+    }
 
-        (string, arr)
+    #[test]
+    fn justify_words() {
+        assert_eq!(justify(&["a", "b", "c"], 8), "a   b  c");
+        assert_eq!(justify(&["漢字", "ab"], 8), "漢字  ab");
+        assert_eq!(justify(&["abc", "def"], 4), "abc def");
     }
 }
@@ -0,0 +1,308 @@
+use crate::*;
+use crate::input_sequences::*;
+
+/// The track and thumb of scrollbars
+pub(crate) const SCROLLBAR: (Static, Static) = ("│", "┃");
+
+/// Draw a vertical scrollbar, if there are more rows than fit
+pub(crate) fn draw_scrollbar(canvas: &mut dyn Canvas, col: Cell, row: Cell, height: Cell,
+        scroll: usize, rows: usize, style: Option<&Style>) {
+
+    let height_rows = height as usize;
+    if rows <= height_rows || height == 0 {
+        return
+    }
+
+    let thumb = (height_rows * height_rows / rows).max(1);
+    let start = (scroll * (height_rows - thumb)).div_ceil(rows - height_rows);
+
+    canvas.draw_vbar(col, row, height, SCROLLBAR.0, style);
+    canvas.draw_vbar(col, row + start as Cell, thumb as Cell, SCROLLBAR.1, style);
+}
+
+/// An item of a `List`
+#[derive(Clone, PartialEq)]
+pub struct ListItem {
+    pub content: Line,
+    /// Used for the spans of the content without a style of their own
+    pub style: Option<Style>,
+}
+
+impl ListItem {
+    pub fn new(content: impl Into<Line>) -> Self {
+        Self {
+            content: content.into(),
+            style: None,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+impl From<&str> for ListItem {
+    fn from(content: &str) -> Self {
+        Self::new(content)
+    }
+}
+
+impl From<Str> for ListItem {
+    fn from(content: Str) -> Self {
+        Self::new(Span::new(content))
+    }
+}
+
+impl From<Line> for ListItem {
+    fn from(content: Line) -> Self {
+        Self::new(content)
+    }
+}
+
+/// Scrollable items, one of which is selected.
+///
+/// Moved through with the arrow keys, `PG_UP`/`PG_DOWN` and `HOME`/`END`. If multiple items can
+/// be marked, the selected item is marked or unmarked with space.
+pub struct List {
+    items: Vec<ListItem>,
+    selected: usize,
+    marked: Vec<bool>,
+    multi_select: bool,
+    /// The first item shown, updated when rendering
+    scroll: std::cell::Cell<usize>,
+    /// The height when last rendered, used for moving by pages
+    height: std::cell::Cell<Cell>,
+    highlight_style: Style,
+    scrollbar_style: Option<Style>,
+}
+
+impl List {
+    pub fn new(items: Vec<impl Into<ListItem>>) -> Self {
+        let items: Vec<ListItem> = items.into_iter().map(Into::into).collect();
+
+        Self {
+            marked: vec![false; items.len()],
+            items,
+            selected: 0,
+            multi_select: false,
+            scroll: std::cell::Cell::new(0),
+            height: std::cell::Cell::new(1),
+            highlight_style: Style::new().fg(Color::Black).bg(Color::White),
+            scrollbar_style: None,
+        }
+    }
+
+    /// Allow marking multiple items
+    pub fn multi_select(mut self) -> Self {
+        self.multi_select = true;
+        self
+    }
+
+    /// Style of the selected item
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn scrollbar_style(mut self, style: Style) -> Self {
+        self.scrollbar_style = Some(style);
+        self
+    }
+
+    pub fn items(&self) -> &[ListItem] {
+        &self.items
+    }
+
+    /// Replace the items, keeping the selection within them and unmarking everything
+    pub fn set_items(&mut self, items: Vec<impl Into<ListItem>>) {
+        self.items = items.into_iter().map(Into::into).collect();
+        self.marked = vec![false; self.items.len()];
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Index of the selected item, unless the list is empty
+    pub fn selected(&self) -> Option<usize> {
+        (!self.items.is_empty()).then_some(self.selected)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Indices of the marked items
+    pub fn marked(&self) -> Vec<usize> {
+        self.marked.iter().enumerate().filter(|(_, marked)| **marked).map(|(i, _)| i).collect()
+    }
+
+    pub fn toggle_mark(&mut self, index: usize) {
+        if let Some(marked) = self.marked.get_mut(index) {
+            *marked = !*marked;
+        }
+    }
+
+    /// Move the selection by some amount of items
+    fn move_by(&mut self, amount: isize) {
+        self.select(self.selected.saturating_add_signed(amount));
+    }
+
+    /// The first item to show, so that the selected item is visible
+    fn scroll_to_selected(&self, height: Cell) -> usize {
+        let height = height.max(1) as usize;
+        let mut scroll = self.scroll.get().min(self.selected);
+        if self.selected >= scroll + height {
+            scroll = self.selected + 1 - height;
+        }
+        scroll.min(self.items.len().saturating_sub(height))
+    }
+}
+
+impl Widget for List {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        let scroll = self.scroll_to_selected(height);
+        self.scroll.set(scroll);
+        self.height.set(height);
+
+        // leave room for the scrollbar
+        let overflows = self.items.len() > height as usize;
+        let width = if overflows { width - 1 } else { width };
+
+        canvas.clear_rect(col, row, width, height);
+
+        for (i, item) in self.items.iter().enumerate().skip(scroll).take(height.into()) {
+            let item_row = row + (i - scroll) as Cell;
+            let highlighted = i == self.selected;
+            let mut line = item.content.clone();
+
+            if self.multi_select {
+                let marker = if self.marked[i] { "● " } else { "○ " };
+                line.spans.insert(0, Span::new(marker));
+            }
+
+            for span in &mut line.spans {
+                if highlighted {
+                    span.style = Some(self.highlight_style.clone());
+                } else if span.style.is_none() {
+                    span.style = item.style.clone();
+                }
+            }
+
+            if highlighted {
+                canvas.draw_hbar(col, item_row, width, " ", Some(&self.highlight_style));
+            }
+            canvas.addline(col, item_row, width, &line);
+        }
+
+        draw_scrollbar(canvas, col + width, row, height, scroll, self.items.len(), self.scrollbar_style.as_ref());
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let bytes = match event {
+            Event::Key(bytes) => bytes,
+            Event::Mouse { row, pressed: true, .. } => {
+                let index = self.scroll.get() + row as usize;
+                if index < self.items.len() {
+                    self.selected = index;
+                    return EventResult::Consumed
+                }
+                return EventResult::Ignored
+            }
+            _ => return EventResult::Ignored,
+        };
+
+        let bytes = &bytes[..];
+        let page = self.height.get().max(1) as isize;
+
+        match bytes {
+            ARROW_UP => self.move_by(-1),
+            ARROW_DOWN => self.move_by(1),
+            PG_UP => self.move_by(-page),
+            PG_DOWN => self.move_by(page),
+            _ if HOME.contains(&bytes) => self.select(0),
+            _ if END.contains(&bytes) => self.select(self.items.len()),
+            b" " if self.multi_select => self.toggle_mark(self.selected),
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (1, 1)
+    }
+
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        let marker = if self.multi_select { 2 } else { 0 };
+        let width = self.items.iter().map(|item| item.content.width()).max().unwrap_or(0);
+        Some((width + marker, self.items.len() as Cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(list: &mut List, bytes: &[u8]) {
+        list.handle_event(Event::Key(bytes.to_vec()));
+    }
+
+    fn render(list: &List, height: Cell) -> InstructionBuffer<'static> {
+        let mut canvas = InstructionBuffer::new(4, height, None);
+        list.render((0, 0, 4, height), &mut canvas);
+        canvas
+    }
+
+    #[test]
+    fn move_by_pages() {
+        let mut list = List::new((0..10).map(|i| i.to_string()).collect());
+        render(&list, 3);
+
+        key(&mut list, PG_DOWN);
+        assert_eq!(list.selected(), Some(3));
+        key(&mut list, ARROW_UP);
+        key(&mut list, PG_UP);
+        assert_eq!(list.selected(), Some(0));
+        key(&mut list, END[0]);
+        assert_eq!(list.selected(), Some(9));
+
+        list.set_items(vec!["a", "b"]);
+        assert_eq!(list.selected(), Some(1));
+        list.set_items(Vec::<Str>::new());
+        assert_eq!(list.selected(), None);
+    }
+
+    #[test]
+    fn scroll_to_the_selection() {
+        let mut list = List::new((0..10).map(|i| i.to_string()).collect());
+        list.select(5);
+        let canvas = render(&list, 3);
+        assert_eq!(canvas.symbol_at(0, 2).as_deref(), Some("5"));
+
+        // clicks are relative to the first item shown
+        list.handle_event(Event::Mouse { button: 0, col: 0, row: 0, pressed: true });
+        assert_eq!(list.selected(), Some(3));
+
+        // the scrollbar takes up the last column
+        assert_eq!(canvas.symbol_at(3, 0).as_deref(), Some(SCROLLBAR.0));
+        assert_eq!(canvas.symbol_at(3, 1).as_deref(), Some(SCROLLBAR.1));
+    }
+
+    #[test]
+    fn mark_items() {
+        let mut list = List::new(vec!["a", "b", "c"]);
+        key(&mut list, b" ");
+        assert_eq!(list.marked(), []);
+
+        let mut list = list.multi_select();
+        key(&mut list, b" ");
+        key(&mut list, ARROW_DOWN);
+        key(&mut list, ARROW_DOWN);
+        key(&mut list, b" ");
+        assert_eq!(list.marked(), [0, 2]);
+        assert_eq!(render(&list, 3).symbol_at(0, 1).as_deref(), Some("○"));
+    }
+}
@@ -0,0 +1,2193 @@
+use crate::*;
+use crate::box_drawing;
+use crate::input_sequences::*;
+use crate::utils::{display_width, truncate};
+
+use std::fmt;
+use std::str::FromStr;
+use std::collections::HashMap;
+
+/// Column, row, width and height
+type Rect = (Cell, Cell, Cell, Cell);
+
+/// The characters and style of a border used in a connected layout
+type ConnectedBorder<'a> = ([&'a str; 11], Option<&'a Style>);
+
+/// Rendered borders, along with every pane and its area
+type Rendered<'a, T> = (Str, Vec<(&'a T, Cell, Cell, Cell, Cell)>);
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PaneSize {
+    Fixed(Cell),
+    Relative(Cell),
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Paner<T> {
+    Pane(T),
+    Horizontal(Vec<(PaneSize, Paner<T>)>),
+    Vertical(Vec<(PaneSize, Paner<T>)>),
+    /// Decorates the border around a pane or subtree
+    Framed(Box<Frame>, Box<Paner<T>>),
+    /// Shows only the active tab, with the names of the tabs drawn on the top border
+    Tabs {
+        active: usize,
+        tabs: Vec<(Str, Paner<T>)>,
+    },
+    /// Children drawn on top of each other, from the bottom up. Every child but the first
+    /// floats on a layer of its own, covering what is below it.
+    Stack(Vec<(Float, Paner<T>)>),
+}
+
+/// Where a child of a `Paner::Stack` is placed, relative to the top left corner of the stack
+/// including its border. The sizes include the border of the child, and are shrunk to fit.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Float {
+    /// Covers the whole stack
+    Fill,
+    /// Column, row, width and height
+    At(Cell, Cell, Cell, Cell),
+    /// Width and height, centered in the stack
+    Centered(Cell, Cell),
+}
+
+impl Float {
+    /// The area of a child inside a stack, given the area of the stack
+    fn rect(&self, rect: Rect) -> Rect {
+        let (col, row, width, height) = rect;
+        let (outer_col, outer_row, outer_width, outer_height) =
+            (col.saturating_sub(1), row.saturating_sub(1), width + 2, height + 2);
+
+        let (col, row, width, height) = match *self {
+            Self::Fill => return rect,
+            Self::At(col, row, width, height) => {
+                let (width, height) = (width.min(outer_width), height.min(outer_height));
+                (col.min(outer_width - width), row.min(outer_height - height), width, height)
+            }
+            Self::Centered(width, height) => {
+                let (width, height) = (width.min(outer_width), height.min(outer_height));
+                ((outer_width - width) / 2, (outer_height - height) / 2, width, height)
+            }
+        };
+
+        (outer_col + col + 1, outer_row + row + 1, width.saturating_sub(2), height.saturating_sub(2))
+    }
+}
+
+/// Text embedded in a border, such as a title
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Label {
+    pub text: Str,
+    pub align: Align,
+    pub style: Option<Style>,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            align: Align::Left,
+            style: None,
+        }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+impl From<&str> for Label {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Decorations drawn on the border of a `Paner::Framed`
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Default)]
+pub struct Frame {
+    /// Drawn on the top border
+    pub title: Option<Label>,
+    /// Drawn on the bottom border
+    pub footer: Option<Label>,
+    /// Replaces the characters of the borders inside the frame. The spacing between panes is
+    /// still decided by the style given to `Paner::render`, so it should be of the same kind.
+    pub border: Option<BorderStyle>,
+    /// Style of the borders inside the frame
+    pub style: Option<Style>,
+}
+
+impl Frame {
+    pub const fn new() -> Self {
+        Self {
+            title: None,
+            footer: None,
+            border: None,
+            style: None,
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<Label>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<Label>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    pub fn border(mut self, border: BorderStyle) -> Self {
+        self.border = Some(border);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+/// A pane laid out for rendering
+struct Placed<'a, T> {
+    pane: &'a T,
+    /// Index of the pane, see `Paner::get`
+    index: usize,
+    rect: Rect,
+    /// Index of the border used around it
+    border: usize,
+    layer: usize,
+}
+
+struct TabBar<'a, T> {
+    tabs: &'a [(Str, Paner<T>)],
+    active: usize,
+    rect: Rect,
+    layer: usize,
+    path: Vec<usize>,
+}
+
+/// Everything needed to render a paner, relative to its top left corner
+struct Layout<'a, 'b, T> {
+    /// Ordered by layer, and then by their order in the tree
+    panes: Vec<Placed<'a, T>>,
+    frames: Vec<(&'a Frame, Rect, usize)>,
+    tab_bars: Vec<TabBar<'a, T>>,
+    /// The area of the children of stacks floating on each layer, except the bottom one
+    layers: Vec<Option<Rect>>,
+    /// Borders and styles used for the borders, the first being the default
+    borders: Vec<(&'b BorderStyle, Option<&'b Style>)>,
+    gap: Cell,
+    /// Path to the node being laid out
+    path: Vec<usize>,
+    /// Index of the next pane, counting the panes of hidden tabs
+    index: usize,
+}
+
+const ACTIVE_TAB: Style = Style::new().bold().underline();
+
+impl<T> Paner<T> {
+    pub fn framed(self, frame: Frame) -> Self {
+        Self::Framed(Box::new(frame), Box::new(self))
+    }
+
+    /// Render the borders, and get the area of every visible pane. Panes floating in stacks are
+    /// returned after the ones below them, but their borders are part of the same string,
+    /// so `Paner::render_layers` should be used to keep lower panes from covering them.
+    pub fn render(
+            &self,
+            start_col: Cell,
+            start_row: Cell,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Rendered<'_, T> {
+
+        let mut string = Str::new();
+        let mut panes = Vec::new();
+
+        for (layer, mut layer_panes) in self.render_layers(start_col, start_row, width, height, border) {
+            string.push_str(&layer);
+            panes.append(&mut layer_panes);
+        }
+
+        (string, panes)
+    }
+
+    /// Like `Paner::render`, but split into layers from the bottom up. Drawing the borders of
+    /// each layer before its panes makes floating panes cover what is below them.
+    pub fn render_layers(
+            &self,
+            start_col: Cell,
+            start_row: Cell,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Vec<Rendered<'_, T>> {
+
+        // everything is laid out relative to the start, and moved when rendered
+        let layout = self.lay_out(width, height, border);
+
+        let borders = connected_borders(&layout, border);
+
+        (0..layout.layers.len()).map(|layer| {
+            let mut canvas = InstructionBuffer::new(width, height, None);
+            let panes = self.draw_layer(&layout, layer, &mut canvas, borders.as_deref());
+
+            let panes = panes.into_iter()
+                .map(|(pane, (col, row, width, height))|
+                    (pane, start_col + col, start_row + row, width, height))
+                .collect();
+
+            (canvas.render(start_col, start_row), panes)
+        }).collect()
+    }
+
+    /// Like `Paner::render`, but drawing the borders of every layer onto a canvas.
+    /// The areas of the panes are relative to the top left corner of the canvas.
+    pub fn draw(
+            &self,
+            canvas: &mut (impl Canvas + ?Sized),
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Vec<(&T, Cell, Cell, Cell, Cell)> {
+
+        let layout = self.lay_out(width, height, border);
+        let borders = connected_borders(&layout, border);
+
+        (0..layout.layers.len())
+            .flat_map(|layer| self.draw_layer(&layout, layer, canvas, borders.as_deref()))
+            .map(|(pane, (col, row, width, height))| (pane, col, row, width, height))
+            .collect()
+    }
+
+    /// Draw the borders, labels and tabs of a layer, returning its panes
+    fn draw_layer<'a>(
+            &self,
+            layout: &Layout<'a, '_, T>,
+            layer: usize,
+            canvas: &mut (impl Canvas + ?Sized),
+            borders: Option<&[ConnectedBorder]>) -> Vec<(&'a T, Rect)> {
+
+        let panes = layout.panes.iter().filter(|placed| placed.layer == layer);
+
+        // cover whatever is below a floating child, including its border
+        if let Some((col, row, width, height)) = layout.layers[layer] {
+            for row in row - 1..row + height + 1 {
+                canvas.draw_hbar(col - 1, row, width + 2, " ", None);
+            }
+        }
+
+        match layout.borders[0].0 {
+            BorderStyle::Gap(..) => {}
+            BorderStyle::Disconnected(..) | BorderStyle::DisconnectedOwned(..) => {
+                for Placed { rect: (col, row, width, height), border, .. } in panes.clone() {
+                    let (border, style) = layout.borders[*border];
+                    canvas.draw_box(col - 1, row - 1, width + 2, height + 2, border, style);
+                }
+            }
+            BorderStyle::Connected(..) | BorderStyle::ConnectedOwned(..) => {
+                let mut grid = BorderGrid::default();
+                for Placed { rect: (col, row, width, height), border, .. } in panes.clone() {
+                    grid.add_box(col - 1, row - 1, width + 2, height + 2, *border);
+                }
+                grid.draw(canvas, borders.unwrap_or_default());
+            }
+        }
+
+        for (frame, (col, row, width, height), _) in layout.frames.iter().filter(|i| i.2 == layer) {
+            if let Some(title) = &frame.title {
+                draw_label(canvas, title, *col, row - 1, *width);
+            }
+            if let Some(footer) = &frame.footer {
+                draw_label(canvas, footer, *col, row + height, *width);
+            }
+        }
+
+        for bar in layout.tab_bars.iter().filter(|bar| bar.layer == layer) {
+            let (col, row, width, _) = bar.rect;
+            for (i, (tab_col, text)) in tab_positions(bar.tabs, col, width).iter().enumerate() {
+                let style = if i == bar.active { Some(&ACTIVE_TAB) } else { None };
+                canvas.addstr(*tab_col, row - 1, text, style);
+            }
+        }
+
+        panes.map(|placed| (placed.pane, placed.rect)).collect()
+    }
+
+    fn lay_out<'a: 'b, 'b>(&'a self, width: Cell, height: Cell, border: &'b BorderStyle) -> Layout<'a, 'b, T> {
+        let mut layout = Layout {
+            panes: Vec::new(),
+            frames: Vec::new(),
+            tab_bars: Vec::new(),
+            layers: vec![None],
+            borders: vec![(border, None)],
+            gap: border.gap(),
+            path: Vec::new(),
+            index: 0,
+        };
+
+        self.layout((1, 1, width.saturating_sub(2), height.saturating_sub(2)), 0, 0, &mut layout);
+        layout.panes.sort_by_key(|placed| placed.layer);
+        layout
+    }
+
+    fn layout<'a: 'b, 'b>(&'a self, rect: Rect, border: usize, layer: usize, layout: &mut Layout<'a, 'b, T>) {
+        let (col, row, width, height) = rect;
+
+        match self {
+            Self::Pane(pane) => {
+                layout.panes.push(Placed { pane, index: layout.index, rect, border, layer });
+                layout.index += 1;
+            }
+            Self::Framed(frame, paner) => {
+                // outer frames go first, so labels of inner ones are drawn on top
+                layout.frames.push((frame.as_ref(), rect, layer));
+
+                // borders added later take precedence where they meet earlier ones
+                let border = if frame.border.is_some() || frame.style.is_some() {
+                    let default = layout.borders[border].0;
+                    layout.borders.push((frame.border.as_ref().unwrap_or(default), frame.style.as_ref()));
+                    layout.borders.len() - 1
+                } else {
+                    border
+                };
+
+                layout.path.push(0);
+                paner.layout(rect, border, layer, layout);
+                layout.path.pop();
+            }
+            Self::Horizontal(paners) | Self::Vertical(paners) => {
+                let horizontal = matches!(self, Self::Horizontal(..));
+                let sizes = split(paners, if horizontal { width } else { height }, layout.gap);
+
+                let mut i = 0;
+                for (j, ((_, paner), size)) in paners.iter().zip(sizes).enumerate() {
+                    let rect = if horizontal {
+                        (col + i, row, size, height)
+                    } else {
+                        (col, row + i, width, size)
+                    };
+
+                    layout.path.push(j);
+                    paner.layout(rect, border, layer, layout);
+                    layout.path.pop();
+                    i += size + layout.gap;
+                }
+            }
+            Self::Tabs { active, tabs } => {
+                let active = (*active).min(tabs.len().saturating_sub(1));
+
+                layout.tab_bars.push(TabBar { tabs, active, rect, layer, path: layout.path.clone() });
+
+                for (i, (_, paner)) in tabs.iter().enumerate() {
+                    if i != active {
+                        // hidden panes keep their indices
+                        layout.index += paner.count();
+                        continue
+                    }
+
+                    layout.path.push(active);
+                    paner.layout(rect, border, layer, layout);
+                    layout.path.pop();
+                }
+            }
+            Self::Stack(paners) => {
+                for (i, (float, paner)) in paners.iter().enumerate() {
+                    let rect = float.rect(rect);
+
+                    let layer = if i == 0 {
+                        layer
+                    } else {
+                        layout.layers.push(Some(rect));
+                        layout.layers.len() - 1
+                    };
+
+                    layout.path.push(i);
+                    paner.layout(rect, border, layer, layout);
+                    layout.path.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Panes are referred to by their index in the tree, from left to right and top to bottom,
+/// including the panes of hidden tabs. Switching tabs does not change the indices.
+impl<T> Paner<T> {
+    /// Every pane, in the order of their indices
+    pub fn panes(&self) -> Vec<&T> {
+        let mut panes = Vec::new();
+        self.visit(true, &mut 0, &mut |_, pane, _| panes.push(pane));
+        panes
+    }
+
+    pub fn panes_mut(&mut self) -> Vec<&mut T> {
+        fn collect<'a, T>(paner: &'a mut Paner<T>, panes: &mut Vec<&'a mut T>) {
+            match paner {
+                Paner::Pane(pane) => panes.push(pane),
+                Paner::Framed(_, paner) => collect(paner, panes),
+                Paner::Horizontal(paners) | Paner::Vertical(paners) => {
+                    paners.iter_mut().for_each(|(_, paner)| collect(paner, panes));
+                }
+                Paner::Tabs { tabs, .. } => tabs.iter_mut().for_each(|(_, paner)| collect(paner, panes)),
+                Paner::Stack(paners) => paners.iter_mut().for_each(|(_, paner)| collect(paner, panes)),
+            }
+        }
+
+        let mut panes = Vec::new();
+        collect(self, &mut panes);
+        panes
+    }
+
+    /// Indices of the panes which are not in hidden tabs
+    pub fn visible(&self) -> Vec<usize> {
+        let mut visible = Vec::new();
+        self.visit(true, &mut 0, &mut |index, _, shown| if shown { visible.push(index) });
+        visible
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        match self.node(&self.path(index)?) {
+            Self::Pane(pane) => Some(pane),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let path = self.path(index)?;
+        match self.node_mut(&path) {
+            Self::Pane(pane) => Some(pane),
+            _ => None,
+        }
+    }
+
+    /// Split a pane in two, placing the new pane in some direction of it.
+    /// Returns the index of the new pane.
+    pub fn split(&mut self, index: usize, direction: Direction, pane: T) -> Option<usize> {
+        let path = self.unit_path(index)?;
+        let node = self.node_mut(&path);
+        let old = std::mem::replace(node, Self::Horizontal(Vec::new()));
+        let new = (PaneSize::Relative(1), Self::Pane(pane));
+
+        let (paners, new_index) = match direction {
+            Direction::Right | Direction::Down => (vec![(PaneSize::Relative(1), old), new], index + 1),
+            Direction::Left | Direction::Up => (vec![new, (PaneSize::Relative(1), old)], index),
+        };
+
+        *node = match direction {
+            Direction::Left | Direction::Right => Self::Horizontal(paners),
+            Direction::Up | Direction::Down => Self::Vertical(paners),
+        };
+
+        Some(new_index)
+    }
+
+    /// Remove a pane, collapsing its parent if needed. The last pane can not be removed.
+    /// Sizes elsewhere are left as they are.
+    pub fn close(&mut self, index: usize) -> Option<T> {
+        if self.panes().len() < 2 {
+            return None
+        }
+
+        let mut path = self.unit_path(index)?;
+        let i = path.pop()?;
+
+        let mut unit = match self.node_mut(&path) {
+            Self::Horizontal(paners) | Self::Vertical(paners) => paners.remove(i).1,
+            Self::Tabs { active, tabs } => {
+                // keep the same tab active
+                if *active > i {
+                    *active -= 1;
+                }
+                *active = (*active).min(tabs.len().saturating_sub(2));
+                tabs.remove(i).1
+            }
+            Self::Stack(paners) => paners.remove(i).1,
+            _ => unreachable!(),
+        };
+
+        self.tidy(path);
+
+        loop {
+            match unit {
+                Self::Pane(pane) => break Some(pane),
+                Self::Framed(_, paner) => unit = *paner,
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// Swap the places of two panes, along with their frames
+    pub fn swap(&mut self, a: usize, b: usize) -> bool {
+        let (Some(a), Some(b)) = (self.unit_path(a), self.unit_path(b)) else {
+            return false
+        };
+
+        if a == b {
+            return true
+        }
+
+        let first = std::mem::replace(self.node_mut(&a), Self::Horizontal(Vec::new()));
+        let second = std::mem::replace(self.node_mut(&b), first);
+        *self.node_mut(&a) = second;
+        true
+    }
+
+    /// Switch to some tab of the tabs closest around a pane
+    pub fn select_tab(&mut self, index: usize, tab: usize) -> bool {
+        let Some(mut path) = self.path(index) else {
+            return false
+        };
+
+        while path.pop().is_some() {
+            if let Self::Tabs { active, tabs } = self.node_mut(&path) {
+                if tab >= tabs.len() {
+                    return false
+                }
+                *active = tab;
+                return true
+            }
+        }
+
+        false
+    }
+
+    /// Find the tab whose name is drawn at some cell, relative to the top left corner.
+    /// The size and border should be the same as given to `Paner::render`.
+    pub fn tab_at(&self, col: Cell, row: Cell, width: Cell, height: Cell, border: &BorderStyle) -> Option<usize> {
+        self.find_tab(col, row, width, height, border).map(|(_, tab)| tab)
+    }
+
+    /// Switch to the tab whose name is drawn at some cell, such as when it is clicked.
+    /// Returns the index of the tab.
+    pub fn select_tab_at(
+            &mut self,
+            col: Cell,
+            row: Cell,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Option<usize> {
+
+        let (path, tab) = self.find_tab(col, row, width, height, border)?;
+
+        if let Self::Tabs { active, .. } = self.node_mut(&path) {
+            *active = tab;
+        }
+
+        Some(tab)
+    }
+
+    fn find_tab(
+            &self,
+            col: Cell,
+            row: Cell,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Option<(Vec<usize>, usize)> {
+
+        let layout = self.lay_out(width, height, border);
+
+        // tabs can be covered by the floating children of stacks
+        let covered = |layer: usize| layout.layers[layer + 1..].iter().flatten()
+            .any(|(c, r, w, h)| (c - 1..c + w + 1).contains(&col) && (r - 1..r + h + 1).contains(&row));
+
+        layout.tab_bars.iter().rev()
+            .filter(|bar| bar.rect.1 == row + 1 && !covered(bar.layer))
+            .find_map(|bar| tab_positions(bar.tabs, bar.rect.0, bar.rect.2).iter()
+                .position(|(tab_col, text)|
+                    (*tab_col..tab_col + display_width(text) as Cell).contains(&col))
+                .map(|tab| (bar.path.clone(), tab)))
+    }
+
+    /// Move an edge of a pane some amount of cells, towards the right or bottom if positive.
+    /// The size and border should be the same as given to `Paner::render`.
+    /// Relative sizes in the affected split are replaced by ones matching the sizes they are
+    /// rendered at. Returns false if the edge can not be moved.
+    pub fn resize(
+            &mut self,
+            index: usize,
+            edge: Direction,
+            delta: i16,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> bool {
+
+        let Some(mut path) = self.path(index) else {
+            return false
+        };
+
+        let horizontal = matches!(edge, Direction::Left | Direction::Right);
+        let gap = border.gap();
+
+        // find the closest split of the right direction with a pane on the other side of the edge
+        let i = loop {
+            let Some(i) = path.pop() else {
+                return false
+            };
+
+            let len = match self.node(&path) {
+                Self::Horizontal(paners) if horizontal => paners.len(),
+                Self::Vertical(paners) if !horizontal => paners.len(),
+                _ => continue,
+            };
+
+            match edge {
+                Direction::Right | Direction::Down if i + 1 < len => break i,
+                Direction::Left | Direction::Up if i > 0 => break i - 1,
+                _ => {}
+            }
+        };
+
+        let (_, _, width, height) = self.rect(&path, (1, 1, width.saturating_sub(2), height.saturating_sub(2)), gap);
+        let length = if horizontal { width } else { height };
+
+        let (Self::Horizontal(paners) | Self::Vertical(paners)) = self.node_mut(&path) else {
+            unreachable!()
+        };
+
+        let sizes = split(paners, length, gap);
+
+        // neither pane may shrink below one cell
+        let delta = match delta {
+            0.. => (delta as Cell).min(sizes[i + 1].saturating_sub(1)) as i32,
+            _ => -((delta.unsigned_abs()).min(sizes[i].saturating_sub(1)) as i32),
+        };
+
+        if delta == 0 {
+            return false
+        }
+
+        let mut actual = sizes.clone();
+        actual[i] = (sizes[i] as i32 + delta) as Cell;
+        actual[i + 1] = (sizes[i + 1] as i32 - delta) as Cell;
+
+        let old: Vec<_> = paners.iter().map(|(size, _)| *size).collect();
+
+        for (j, (size, _)) in paners.iter_mut().enumerate() {
+            if let PaneSize::Fixed(size) = size && (j == i || j == i + 1) {
+                *size = actual[j];
+            }
+        }
+
+        // a relative size is rendered that much smaller, so that one cell is left for the border
+        // when there is no gap, and the last pane is stretched to fill what is left
+        let total_fixed: Cell = paners.iter()
+            .map(|i| if let PaneSize::Fixed(size) = i.0 { size } else { 0 }).sum();
+        let mut space = length.saturating_sub(total_fixed);
+        let last = paners.len() - 1;
+
+        for (j, (size, _)) in paners.iter_mut().enumerate() {
+            if let PaneSize::Relative(size) = size && j != last {
+                *size = actual[j].saturating_add(gap).saturating_add(1);
+                space = space.saturating_sub(*size);
+            }
+        }
+        if let (PaneSize::Relative(size), _) = &mut paners[last] {
+            *size = space;
+        }
+
+        // rounding may keep the sizes from changing
+        if split(paners, length, gap) == sizes {
+            for ((size, _), old) in paners.iter_mut().zip(old) {
+                *size = old;
+            }
+            return false
+        }
+
+        true
+    }
+
+    /// Find the pane and edge for a separator at some cell, relative to the top left corner.
+    /// Useful for resizing panes by dragging their borders with the mouse.
+    pub fn separator_at(
+            &self,
+            col: Cell,
+            row: Cell,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> Option<(usize, Direction)> {
+
+        let gap = border.gap();
+
+        // the outer border can not be moved
+        if col + 1 >= width || row + 1 >= height {
+            return None
+        }
+
+        let rects = self.rects(width, height, border);
+
+        rects.iter()
+            .find(|(_, (c, r, w, h))| (*r..r + h).contains(&row) && (c + w..c + w + gap).contains(&col))
+            .map(|(i, _)| (*i, Direction::Right))
+            .or_else(|| rects.iter()
+                .find(|(_, (c, r, w, h))| (*c..c + w).contains(&col) && (r + h..r + h + gap).contains(&row))
+                .map(|(i, _)| (*i, Direction::Down)))
+    }
+
+    /// The index and area of every visible pane, relative to the top left corner,
+    /// in the order they are rendered
+    fn rects(&self, width: Cell, height: Cell, border: &BorderStyle) -> Vec<(usize, Rect)> {
+        self.lay_out(width, height, border).panes.into_iter().map(|placed| (placed.index, placed.rect)).collect()
+    }
+
+    /// The area of the node at some path
+    fn rect(&self, path: &[usize], rect: Rect, gap: Cell) -> Rect {
+        let Some((i, path)) = path.split_first() else {
+            return rect
+        };
+
+        let (col, row, width, height) = rect;
+
+        match self {
+            Self::Pane(..) => rect,
+            Self::Framed(_, paner) => paner.rect(path, rect, gap),
+            Self::Tabs { tabs, .. } => tabs[*i].1.rect(path, rect, gap),
+            Self::Stack(paners) => paners[*i].1.rect(path, paners[*i].0.rect(rect), gap),
+            Self::Horizontal(paners) | Self::Vertical(paners) => {
+                let horizontal = matches!(self, Self::Horizontal(..));
+                let sizes = split(paners, if horizontal { width } else { height }, gap);
+                let offset = sizes[..*i].iter().map(|size| size + gap).sum::<Cell>();
+
+                let rect = if horizontal {
+                    (col + offset, row, sizes[*i], height)
+                } else {
+                    (col, row + offset, width, sizes[*i])
+                };
+
+                paners[*i].1.rect(path, rect, gap)
+            }
+        }
+    }
+
+    fn node(&self, path: &[usize]) -> &Self {
+        path.iter().fold(self, |node, i| match node {
+            Self::Pane(..) => node,
+            Self::Framed(_, paner) => paner,
+            Self::Horizontal(paners) | Self::Vertical(paners) => &paners[*i].1,
+            Self::Tabs { tabs, .. } => &tabs[*i].1,
+            Self::Stack(paners) => &paners[*i].1,
+        })
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> &mut Self {
+        let mut node = self;
+
+        for i in path {
+            node = match node {
+                Self::Pane(..) => return node,
+                Self::Framed(_, paner) => paner,
+                Self::Horizontal(paners) | Self::Vertical(paners) => &mut paners[*i].1,
+                Self::Tabs { tabs, .. } => &mut tabs[*i].1,
+                Self::Stack(paners) => &mut paners[*i].1,
+            };
+        }
+
+        node
+    }
+
+    /// The path to a pane, as indices of children. Frames count as having one child.
+    fn path(&self, index: usize) -> Option<Vec<usize>> {
+        fn find<T>(paner: &Paner<T>, index: &mut usize, path: &mut Vec<usize>) -> bool {
+            let children: Vec<&Paner<T>> = match paner {
+                Paner::Pane(..) if *index == 0 => return true,
+                Paner::Pane(..) => {
+                    *index -= 1;
+                    return false
+                }
+                Paner::Framed(_, paner) => vec![paner],
+                Paner::Horizontal(paners) | Paner::Vertical(paners) => paners.iter().map(|(_, paner)| paner).collect(),
+                Paner::Tabs { tabs, .. } => tabs.iter().map(|(_, paner)| paner).collect(),
+                Paner::Stack(paners) => paners.iter().map(|(_, paner)| paner).collect(),
+            };
+
+            for (i, child) in children.into_iter().enumerate() {
+                path.push(i);
+                if find(child, index, path) {
+                    return true
+                }
+                path.pop();
+            }
+
+            false
+        }
+
+        let mut path = Vec::new();
+        find(self, &mut { index }, &mut path).then_some(path)
+    }
+
+    /// Call a function with the index of every pane, and whether it is shown
+    /// given whether this node is
+    fn visit<'a>(&'a self, shown: bool, index: &mut usize, f: &mut impl FnMut(usize, &'a T, bool)) {
+        match self {
+            Self::Pane(pane) => {
+                f(*index, pane, shown);
+                *index += 1;
+            }
+            Self::Framed(_, paner) => paner.visit(shown, index, f),
+            Self::Horizontal(paners) | Self::Vertical(paners) => {
+                paners.iter().for_each(|(_, paner)| paner.visit(shown, index, f));
+            }
+            Self::Tabs { active, tabs } => {
+                let active = (*active).min(tabs.len().saturating_sub(1));
+                for (i, (_, paner)) in tabs.iter().enumerate() {
+                    paner.visit(shown && i == active, index, f);
+                }
+            }
+            Self::Stack(paners) => paners.iter().for_each(|(_, paner)| paner.visit(shown, index, f)),
+        }
+    }
+
+    /// The amount of panes
+    fn count(&self) -> usize {
+        let mut count = 0;
+        self.visit(true, &mut count, &mut |_, _, _| {});
+        count
+    }
+
+    /// The path to a pane including the frames directly around it
+    fn unit_path(&self, index: usize) -> Option<Vec<usize>> {
+        let mut path = self.path(index)?;
+
+        while !path.is_empty() && matches!(self.node(&path[..path.len() - 1]), Self::Framed(..)) {
+            path.pop();
+        }
+
+        Some(path)
+    }
+
+    /// Remove the node at a path if it is left empty, going up through its parents, and
+    /// collapse the first one left that has a single child
+    fn tidy(&mut self, mut path: Vec<usize>) {
+        loop {
+            let node = self.node_mut(&path);
+            let empty = match node {
+                Self::Framed(_, paner) => paner.is_empty(),
+                _ => node.is_empty(),
+            };
+
+            if !empty {
+                match node {
+                    Self::Horizontal(paners) | Self::Vertical(paners) if paners.len() == 1 => {
+                        *node = paners.pop().unwrap().1;
+                    }
+                    Self::Stack(paners) if paners.len() == 1 && paners[0].0 == Float::Fill => {
+                        *node = paners.pop().unwrap().1;
+                    }
+                    _ => {}
+                }
+                return
+            }
+
+            let Some(i) = path.pop() else {
+                return
+            };
+
+            match self.node_mut(&path) {
+                Self::Horizontal(paners) | Self::Vertical(paners) => { paners.remove(i); }
+                Self::Stack(paners) => { paners.remove(i); }
+                Self::Tabs { active, tabs } => {
+                    if *active > i {
+                        *active -= 1;
+                    }
+                    tabs.remove(i);
+                    *active = (*active).min(tabs.len().saturating_sub(1));
+                }
+                node @ Self::Framed(..) => *node = Self::Horizontal(Vec::new()),
+                Self::Pane(..) => unreachable!(),
+            }
+        }
+    }
+
+    /// Remove empty splits, collapse splits with a single child, and merge splits into
+    /// parents of the same direction when all their sizes are relative.
+    /// Relative sizes throughout the tree are rewritten, since merged splits are scaled and
+    /// sizes are divided by their greatest common divisor.
+    pub fn normalize(&mut self) {
+        let horizontal = matches!(self, Self::Horizontal(..));
+
+        let paners = match self {
+            Self::Pane(..) => return,
+            Self::Framed(_, paner) => {
+                paner.normalize();
+                if paner.is_empty() {
+                    *self = Self::Horizontal(Vec::new());
+                }
+                return
+            }
+            Self::Tabs { active, tabs } => {
+                for (_, paner) in tabs.iter_mut() {
+                    paner.normalize();
+                }
+                tabs.retain(|(_, paner)| !paner.is_empty());
+                *active = (*active).min(tabs.len().saturating_sub(1));
+
+                if tabs.is_empty() {
+                    *self = Self::Horizontal(Vec::new());
+                }
+                return
+            }
+            Self::Stack(paners) => {
+                for (_, paner) in paners.iter_mut() {
+                    paner.normalize();
+                }
+                paners.retain(|(_, paner)| !paner.is_empty());
+
+                if paners.len() == 1 && paners[0].0 == Float::Fill {
+                    *self = paners.pop().unwrap().1;
+                } else if paners.is_empty() {
+                    *self = Self::Horizontal(Vec::new());
+                }
+                return
+            }
+            Self::Horizontal(paners) | Self::Vertical(paners) => paners,
+        };
+
+        for (_, paner) in paners.iter_mut() {
+            paner.normalize();
+        }
+
+        paners.retain(|(_, paner)| !paner.is_empty());
+
+        let mut i = 0;
+        while i < paners.len() {
+            let mergeable = match &paners[i] {
+                (PaneSize::Relative(..), Self::Horizontal(children)) if horizontal => all_relative(children),
+                (PaneSize::Relative(..), Self::Vertical(children)) if !horizontal => all_relative(children),
+                _ => false,
+            };
+
+            if !mergeable {
+                i += 1;
+                continue
+            }
+
+            let (size, Self::Horizontal(children) | Self::Vertical(children)) = paners.remove(i) else {
+                unreachable!()
+            };
+            let PaneSize::Relative(size) = size else { unreachable!() };
+
+            // scale the siblings up, so the children can be given a share of the size
+            let total: Cell = children.iter()
+                .map(|i| if let PaneSize::Relative(size) = i.0 { size } else { 0 }).sum();
+
+            for (sibling, _) in paners.iter_mut() {
+                if let PaneSize::Relative(sibling) = sibling {
+                    *sibling = sibling.saturating_mul(total.max(1));
+                }
+            }
+
+            let len = children.len();
+            for (j, (child_size, child)) in children.into_iter().enumerate() {
+                let PaneSize::Relative(child_size) = child_size else { unreachable!() };
+                paners.insert(i + j, (PaneSize::Relative(size.saturating_mul(child_size)), child));
+            }
+
+            i += len;
+        }
+
+        // keep relative sizes small by dividing them by their greatest common divisor
+        let divisor = paners.iter()
+            .filter_map(|(size, _)| if let PaneSize::Relative(size) = size { Some(*size) } else { None })
+            .fold(0, gcd);
+
+        if divisor > 1 {
+            for (size, _) in paners.iter_mut() {
+                if let PaneSize::Relative(size) = size {
+                    *size /= divisor;
+                }
+            }
+        }
+
+        if paners.len() == 1 {
+            *self = paners.pop().unwrap().1;
+        }
+    }
+
+}
+
+impl<T> Paner<T> {
+    /// Whether this is a node without any children, which normalizing removes
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Horizontal(paners) | Self::Vertical(paners) => paners.is_empty(),
+            Self::Tabs { tabs, .. } => tabs.is_empty(),
+            Self::Stack(paners) => paners.is_empty(),
+            Self::Pane(..) | Self::Framed(..) => false,
+        }
+    }
+}
+
+fn all_relative<T>(paners: &[(PaneSize, Paner<T>)]) -> bool {
+    paners.iter().all(|(size, _)| matches!(size, PaneSize::Relative(..)))
+}
+
+fn gcd(a: Cell, b: Cell) -> Cell {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Keeps track of the focused pane of a paner, by its index
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Default, Clone, Copy)]
+pub struct Focus {
+    index: usize,
+}
+
+impl Focus {
+    pub const fn new() -> Self {
+        Self { index: 0 }
+    }
+
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn set<T>(&mut self, paner: &Paner<T>, index: usize) {
+        self.index = index;
+        self.clamp(paner);
+    }
+
+    /// Keep the focus on a visible pane, such as after closing one or switching tabs,
+    /// by focusing the closest one if needed
+    pub fn clamp<T>(&mut self, paner: &Paner<T>) {
+        let visible = paner.visible();
+        if let Some(closest) = visible.iter().min_by_key(|index| index.abs_diff(self.index)) {
+            self.index = *closest;
+        }
+    }
+
+    /// Focus the next visible pane, wrapping around after the last
+    pub fn next<T>(&mut self, paner: &Paner<T>) {
+        let visible = paner.visible();
+        let next = visible.iter().find(|index| **index > self.index).or(visible.first());
+        self.index = next.copied().unwrap_or(0);
+    }
+
+    /// Focus the previous visible pane, wrapping around before the first
+    pub fn previous<T>(&mut self, paner: &Paner<T>) {
+        let visible = paner.visible();
+        let previous = visible.iter().rfind(|index| **index < self.index).or(visible.last());
+        self.index = previous.copied().unwrap_or(0);
+    }
+
+    /// Focus the closest pane in some direction of the focused one, preferring panes that are
+    /// directly next to it. The size and border should be the same as given to `Paner::render`.
+    /// Returns false if there is no pane in that direction.
+    pub fn go<T>(
+            &mut self,
+            paner: &Paner<T>,
+            direction: Direction,
+            width: Cell,
+            height: Cell,
+            border: &BorderStyle) -> bool {
+
+        let rects = paner.rects(width, height, border);
+        let Some(&(_, (col, row, w, h))) = rects.iter().find(|(index, _)| *index == self.index) else {
+            return false
+        };
+
+        let closest = rects.iter()
+            .filter_map(|&(i, (c, r, cw, ch))| {
+                // distance in the direction, and how much the panes overlap across it
+                let (distance, overlap) = match direction {
+                    Direction::Left => ((col as i32) - (c + cw) as i32, overlap(r, ch, row, h)),
+                    Direction::Right => ((c as i32) - (col + w) as i32, overlap(r, ch, row, h)),
+                    Direction::Up => ((row as i32) - (r + ch) as i32, overlap(c, cw, col, w)),
+                    Direction::Down => ((r as i32) - (row + h) as i32, overlap(c, cw, col, w)),
+                };
+
+                (i != self.index && distance >= 0 && overlap > 0).then_some((distance, -overlap, i))
+            })
+            .min();
+
+        match closest {
+            Some((_, _, i)) => {
+                self.index = i;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn overlap(a: Cell, a_len: Cell, b: Cell, b_len: Cell) -> i32 {
+    (a + a_len).min(b + b_len) as i32 - a.max(b) as i32
+}
+
+impl Direction {
+    /// The direction of `CTRL_ARROW_*` input sequences, commonly used for moving between panes
+    pub fn from_ctrl_arrow(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            CTRL_ARROW_LEFT => Some(Self::Left),
+            CTRL_ARROW_RIGHT => Some(Self::Right),
+            CTRL_ARROW_UP => Some(Self::Up),
+            CTRL_ARROW_DOWN => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+/// A layout of widgets, drawn and given input without keeping track of their areas
+impl<T: Widget> Paner<T> {
+    /// Draw the borders and every visible widget onto a canvas covering the whole layout
+    pub fn render_widgets(&self, canvas: &mut dyn Canvas, width: Cell, height: Cell, border: &BorderStyle) {
+        let layout = self.lay_out(width, height, border);
+        let borders = connected_borders(&layout, border);
+
+        // floating panes are drawn over the widgets below them
+        for layer in 0..layout.layers.len() {
+            for (widget, area) in self.draw_layer(&layout, layer, canvas, borders.as_deref()) {
+                widget.render(area, canvas);
+            }
+        }
+    }
+
+    /// Pass an event to the widget it is meant for. Mouse events go to the pane below the
+    /// mouse, which is focused when a button is pressed, with the column and row made relative
+    /// to the pane. Resizes go to every pane, and anything else to the focused pane.
+    /// The area is where the layout is shown on the screen, such as the start given to
+    /// `InstructionBuffer::render` along with the size given to `Paner::render_widgets`.
+    pub fn handle_event(
+            &mut self,
+            focus: &mut Focus,
+            event: Event,
+            (start_col, start_row, width, height): Area,
+            border: &BorderStyle) -> EventResult {
+
+        let (index, event) = match event {
+            Event::Mouse { button, col, row, pressed } => {
+                // mouse events are relative to the screen, and the layout to its start
+                let (Some(col), Some(row)) = (col.checked_sub(start_col), row.checked_sub(start_row)) else {
+                    return EventResult::Ignored
+                };
+
+                // the topmost pane is rendered last
+                let rects = self.rects(width, height, border);
+                let Some(&(index, (pane_col, pane_row, _, _))) = rects.iter().rev()
+                    .find(|(_, (pane_col, pane_row, width, height))|
+                        (*pane_col..pane_col + width).contains(&col) && (*pane_row..pane_row + height).contains(&row))
+                else {
+                    return EventResult::Ignored
+                };
+
+                if pressed {
+                    focus.index = index;
+                }
+
+                (index, Event::Mouse { button, col: col - pane_col, row: row - pane_row, pressed })
+            }
+            Event::Resize(..) => {
+                let mut result = EventResult::Ignored;
+                for widget in self.panes_mut() {
+                    if widget.handle_event(event.clone()) == EventResult::Consumed {
+                        result = EventResult::Consumed;
+                    }
+                }
+                return result
+            }
+            event => (focus.index(), event),
+        };
+
+        match self.get_mut(index) {
+            Some(widget) => widget.handle_event(event),
+            None => EventResult::Ignored,
+        }
+    }
+}
+
+/// Written as a number for relative sizes, and with a leading `=` for fixed sizes
+impl fmt::Display for PaneSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Relative(size) => write!(f, "{size}"),
+            Self::Fixed(size) => write!(f, "={size}"),
+        }
+    }
+}
+
+impl FromStr for PaneSize {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input: string, position: 0 };
+        let size = parser.size()?;
+        parser.end()?;
+        Ok(size)
+    }
+}
+
+/// Written as `fill`, as the width and height such as `20x8` when centered,
+/// or followed by the column and row such as `20x8+2+1`
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Fill => write!(f, "fill"),
+            Self::Centered(width, height) => write!(f, "{width}x{height}"),
+            Self::At(col, row, width, height) => write!(f, "{width}x{height}+{col}+{row}"),
+        }
+    }
+}
+
+impl FromStr for Float {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input: string, position: 0 };
+        let float = parser.float()?;
+        parser.end()?;
+        Ok(float)
+    }
+}
+
+/// Written as comma separated attributes, such as `title="Build output",footer-align=right`.
+/// The title and footer are written as `title` and `footer`, their alignment as `title-align`
+/// and `footer-align`, and their style as `title-style` and `footer-style`, while the border
+/// and style of the frame are written as `border` and `style`. Styles are written like the
+/// tags of `Text::from_markup`.
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let mut attributes: Vec<(Str, Str)> = Vec::new();
+
+        for (key, label) in [("title", &self.title), ("footer", &self.footer)] {
+            let Some(label) = label else {
+                continue
+            };
+
+            attributes.push((key.to_string(), label.text.clone()));
+            if label.align != Align::Left {
+                let align = ALIGNS.iter().find(|(_, align)| *align == label.align).map_or("left", |(name, _)| name);
+                attributes.push((format!("{key}-align"), align.to_string()));
+            }
+            if let Some(style) = &label.style {
+                attributes.push((format!("{key}-style"), write_tag(style)));
+            }
+        }
+
+        if let Some(border) = &self.border {
+            attributes.push(("border".to_string(), border.to_string()));
+        }
+        if let Some(style) = &self.style {
+            attributes.push(("style".to_string(), write_tag(style)));
+        }
+
+        for (i, (key, value)) in attributes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{key}=")?;
+            write_name(f, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Frame {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input: string, position: 0 };
+        let frame = parser.frame()?;
+        parser.end()?;
+        Ok(frame)
+    }
+}
+
+/// Names of alignments in frame attributes
+const ALIGNS: [(Static, Align); 4] = [
+    ("left", Align::Left),
+    ("center", Align::Center),
+    ("right", Align::Right),
+    ("justify", Align::Justify),
+];
+
+/// Write a name of a pane or tab, or a value of an attribute, quoting it if it could be
+/// mistaken for syntax
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> Result<(), fmt::Error> {
+    if name.is_empty() || name.contains(|ch: char| "[],:*=\"\\".contains(ch) || ch.is_whitespace()) {
+        write!(f, "\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        write!(f, "{name}")
+    }
+}
+
+/// Written as a compact layout description, where splits are written as `h[...]` and `v[...]`
+/// containing comma separated sizes and panes, such as `h[2:v[1:a,1:b],=30:c]`.
+/// Tabs are written as `t[...]` containing names and panes, with the active tab marked by `*`,
+/// such as `t[*logs:a,"build output":b]`, and stacks as `s[...]` containing floats and panes,
+/// such as `s[fill:a,40x10:b]`. Frames are written as `f[...]` containing the attributes of
+/// the frame and the pane inside of it, such as `f[title=Logs,border=connected-heavy:a]`.
+/// Names of panes and tabs are quoted if they contain whitespace or syntax.
+impl<T: fmt::Display> fmt::Display for Paner<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Pane(pane) => write_name(f, &pane.to_string()),
+            Self::Framed(frame, paner) => write!(f, "f[{frame}:{paner}]"),
+            Self::Horizontal(paners) | Self::Vertical(paners) => {
+                write!(f, "{}[", if matches!(self, Self::Horizontal(..)) { "h" } else { "v" })?;
+
+                for (i, (size, paner)) in paners.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{size}:{paner}")?;
+                }
+
+                write!(f, "]")
+            }
+            Self::Tabs { active, tabs } => {
+                write!(f, "t[")?;
+
+                for (i, (name, paner)) in tabs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    if i == *active {
+                        write!(f, "*")?;
+                    }
+
+                    write_name(f, name)?;
+                    write!(f, ":{paner}")?;
+                }
+
+                write!(f, "]")
+            }
+            Self::Stack(paners) => {
+                write!(f, "s[")?;
+
+                for (i, (float, paner)) in paners.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{float}:{paner}")?;
+                }
+
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl<T: FromStr> FromStr for Paner<T> {
+    type Err = ParseError;
+
+    fn from_str(string: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { input: string, position: 0 };
+        let paner = parser.paner()?;
+        parser.end()?;
+        Ok(paner)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: Static) -> ParseError {
+        ParseError { position: self.position, message }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.input[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), ParseError> {
+        if self.peek() != Some(ch) {
+            return Err(self.error(match ch {
+                ':' => "expected ':'",
+                '=' => "expected '='",
+                ',' => "expected ',' or ']'",
+                ']' => "expected ']'",
+                _ => "unexpected character",
+            }))
+        }
+
+        self.position += ch.len_utf8();
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(..) => Err(self.error("unexpected trailing text")),
+            None => Ok(()),
+        }
+    }
+
+    /// Text up to the next delimiter or whitespace
+    fn token(&mut self) -> &'a str {
+        self.peek();
+        let rest = &self.input[self.position..];
+        let len = rest.find(|ch: char| "[],:".contains(ch) || ch.is_whitespace()).unwrap_or(rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    fn size(&mut self) -> Result<PaneSize, ParseError> {
+        let fixed = self.peek() == Some('=');
+        if fixed {
+            self.position += 1;
+        }
+
+        let position = self.position;
+        let size = self.token().parse()
+            .map_err(|_| ParseError { position, message: "expected a size" })?;
+
+        Ok(if fixed { PaneSize::Fixed(size) } else { PaneSize::Relative(size) })
+    }
+
+    fn float(&mut self) -> Result<Float, ParseError> {
+        self.peek();
+        let position = self.position;
+        let token = self.token();
+        let error = ParseError { position, message: "expected a float" };
+
+        if token == "fill" {
+            return Ok(Float::Fill)
+        }
+
+        // `WxH`, optionally followed by `+COL+ROW`
+        let number = |number: &str| number.bytes().all(|byte| byte.is_ascii_digit())
+            .then(|| number.parse::<Cell>().ok()).flatten().ok_or(error.clone());
+        let (size, at) = match token.split_once('+') {
+            Some((size, at)) => (size, Some(at)),
+            None => (token, None),
+        };
+        let (width, height) = size.split_once('x').ok_or(error.clone())?;
+        let (width, height) = (number(width)?, number(height)?);
+
+        match at.map(|at| at.split_once('+')) {
+            None => Ok(Float::Centered(width, height)),
+            Some(Some((col, row))) => Ok(Float::At(number(col)?, number(row)?, width, height)),
+            Some(None) => Err(error),
+        }
+    }
+
+    /// A name of a pane or tab, or a value of an attribute, which can be quoted
+    fn name(&mut self) -> Result<Str, ParseError> {
+        if self.peek() != Some('"') {
+            let position = self.position;
+            let token = self.token();
+
+            if token.is_empty() {
+                return Err(ParseError { position, message: "expected a name" })
+            }
+
+            return Ok(token.to_string())
+        }
+
+        let mut name = Str::new();
+        let mut chars = self.input[self.position + 1..].char_indices();
+
+        loop {
+            match chars.next() {
+                Some((i, '"')) => {
+                    self.position += i + 2;
+                    return Ok(name)
+                }
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, ch)) => name.push(ch),
+                    None => break,
+                }
+                Some((_, ch)) => name.push(ch),
+                None => break,
+            }
+        }
+
+        Err(self.error("unterminated name"))
+    }
+
+    /// The comma separated attributes of a frame
+    fn frame(&mut self) -> Result<Frame, ParseError> {
+        let mut frame = Frame::new();
+        let mut first = true;
+
+        while !matches!(self.peek(), Some(':') | None) {
+            if !first {
+                self.expect(',')?;
+            }
+            first = false;
+
+            self.peek();
+            let position = self.position;
+            let rest = &self.input[self.position..];
+            let key = &rest[..rest.find(|ch: char| "[],:=\"".contains(ch) || ch.is_whitespace()).unwrap_or(rest.len())];
+            self.position += key.len();
+            self.expect('=')?;
+
+            self.peek();
+            let value_position = self.position;
+            let value = self.name()?;
+            let error = |message| ParseError { position: value_position, message };
+
+            let label = match key {
+                "title" | "title-align" | "title-style" => &mut frame.title,
+                "footer" | "footer-align" | "footer-style" => &mut frame.footer,
+                "border" => {
+                    frame.border = Some(value.parse().map_err(|_| error("invalid border style"))?);
+                    continue
+                }
+                "style" => {
+                    frame.style = Some(parse_tag(&value, 0, Style::new()).map_err(|_| error("invalid style"))?);
+                    continue
+                }
+                _ => return Err(ParseError { position, message: "unknown frame attribute" }),
+            };
+
+            let label = label.get_or_insert_with(|| Label::new(""));
+            match key.split_once('-') {
+                None => label.text = value,
+                Some((_, "align")) => {
+                    label.align = ALIGNS.iter().find(|(name, _)| *name == value).map(|(_, align)| *align)
+                        .ok_or(error("invalid alignment"))?;
+                }
+                Some(..) => {
+                    label.style = Some(parse_tag(&value, 0, Style::new()).map_err(|_| error("invalid style"))?);
+                }
+            }
+        }
+
+        Ok(frame)
+    }
+
+    /// The comma separated children of a split, tabs or stack, after its opening bracket
+    fn entries<K, T: FromStr>(
+            &mut self,
+            mut key: impl FnMut(&mut Self) -> Result<K, ParseError>) -> Result<Vec<(K, Paner<T>)>, ParseError> {
+
+        let mut entries = Vec::new();
+
+        while self.peek() != Some(']') {
+            if !entries.is_empty() {
+                self.expect(',')?;
+            }
+
+            let key = key(self)?;
+            self.expect(':')?;
+            entries.push((key, self.paner()?));
+        }
+
+        self.expect(']')?;
+        Ok(entries)
+    }
+
+    fn paner<T: FromStr>(&mut self) -> Result<Paner<T>, ParseError> {
+        // quoted names are always panes
+        if self.peek() == Some('"') {
+            let position = self.position;
+            return self.name()?.parse().map(Paner::Pane)
+                .map_err(|_| ParseError { position, message: "invalid pane" })
+        }
+
+        let position = self.position;
+        let token = self.token();
+
+        if ["h", "v", "t", "s", "f"].contains(&token) && self.peek() == Some('[') {
+            self.position += 1;
+
+            match token {
+                "f" => {
+                    let frame = self.frame()?;
+                    self.expect(':')?;
+                    let paner = self.paner()?;
+                    self.expect(']')?;
+                    Ok(paner.framed(frame))
+                }
+                "h" => Ok(Paner::Horizontal(self.entries(Self::size)?)),
+                "v" => Ok(Paner::Vertical(self.entries(Self::size)?)),
+                "t" => {
+                    let tabs = self.entries(|parser| {
+                        let active = parser.peek() == Some('*');
+                        if active {
+                            parser.position += 1;
+                        }
+                        Ok((active, parser.name()?))
+                    })?;
+
+                    Ok(Paner::Tabs {
+                        active: tabs.iter().position(|((active, _), _)| *active).unwrap_or(0),
+                        tabs: tabs.into_iter().map(|((_, name), paner)| (name, paner)).collect(),
+                    })
+                }
+                _ => Ok(Paner::Stack(self.entries(Self::float)?)),
+            }
+
+        } else if token.is_empty() {
+            Err(ParseError { position, message: "expected a pane or split" })
+
+        } else {
+            token.parse().map(Paner::Pane)
+                .map_err(|_| ParseError { position, message: "invalid pane" })
+        }
+    }
+}
+
+/// The characters and styles of every border used in a connected layout
+fn connected_borders<'b, T>(
+        layout: &Layout<'_, 'b, T>,
+        border: &'b BorderStyle) -> Option<Vec<ConnectedBorder<'b>>> {
+
+    let chars = border.connected_chars()?;
+
+    // borders of another kind can not form junctions, so the default is used instead
+    Some(layout.borders.iter()
+        .map(|(border, style)| (border.connected_chars().unwrap_or(chars), *style))
+        .collect())
+}
+
+/// Divide a length between panes, leaving a gap between each of them
+fn split<T>(paners: &[(PaneSize, Paner<T>)], length: Cell, gap: Cell) -> Vec<Cell> {
+    let total_rel: u32 = paners.iter()
+        .map(|i| if let PaneSize::Relative(size) = i.0 { size as u32 } else { 0 }).sum();
+    let total_fixed: u32 = paners.iter()
+        .map(|i| if let PaneSize::Fixed(size) = i.0 { size as u32 } else { 0 }).sum();
+
+    let space = (length as u32).saturating_sub(total_fixed);
+
+    let mut i: Cell = 0;
+    paners.iter().enumerate()
+        .map(|(j, (size, _))| {
+            let size = match *size {
+                PaneSize::Relative(size) => {
+                    let size = (space * size as u32).checked_div(total_rel).unwrap_or(0) as Cell;
+                    let size = size.saturating_sub(gap).saturating_sub(1);
+
+                    // extend the last element if it can not be perfect
+                    // TODO improve this and make it even
+                    if j == paners.len() - 1 {
+                        size + length.saturating_sub(size.saturating_add(i))
+                    } else {
+                        size
+                    }
+                }
+                PaneSize::Fixed(size) => size,
+            };
+
+            // never go past the end
+            let size = size.min(length.saturating_sub(i));
+            i = i.saturating_add(size).saturating_add(gap);
+            size
+        })
+        .collect()
+}
+
+/// Draw a label on a horizontal border, between the corners of a pane of some width
+fn draw_label(canvas: &mut (impl Canvas + ?Sized), label: &Label, col: Cell, row: Cell, width: Cell) {
+    // keep a line character between the label and each corner
+    let space = width.saturating_sub(2);
+    if space < 3 {
+        return
+    }
+
+    let text = format!(" {} ", truncate(&label.text, space - 2));
+    let len = display_width(&text) as Cell;
+
+    let offset = match label.align {
+        Align::Left | Align::Justify => 0,
+        Align::Center => (space - len) / 2,
+        Align::Right => space - len,
+    };
+
+    canvas.addstr(col + 1 + offset, row, &text, label.style.as_ref());
+}
+
+/// The column and text of each tab name drawn on the top border, given the area of the tabs
+fn tab_positions<T>(tabs: &[(Str, Paner<T>)], col: Cell, width: Cell) -> Vec<(Cell, Str)> {
+    // keep a line character between the names and each corner
+    let end = col + width.saturating_sub(1);
+    let mut tab_col = col + 1;
+    let mut positions = Vec::new();
+
+    for (name, _) in tabs {
+        let space = end.saturating_sub(tab_col);
+        if space < 3 {
+            break
+        }
+
+        let text = format!(" {} ", truncate(name, space - 2));
+        let len = display_width(&text) as Cell;
+        positions.push((tab_col, text));
+        tab_col += len + 1;
+    }
+
+    positions
+}
+
+const UP: u8 = 1;
+const RIGHT: u8 = 2;
+const DOWN: u8 = 4;
+const LEFT: u8 = 8;
+
+/// Border lines of every pane, merged so that shared edges form junctions
+#[derive(Default)]
+struct BorderGrid {
+    /// The border used by the line going up, right, down and left from each cell,
+    /// keyed by row and column
+    cells: HashMap<(Cell, Cell), [Option<usize>; 4]>,
+}
+
+impl BorderGrid {
+    fn add(&mut self, col: Cell, row: Cell, arms: u8, border: usize) {
+        let cell = self.cells.entry((row, col)).or_default();
+
+        for (i, arm) in cell.iter_mut().enumerate() {
+            if arms & (1 << i) != 0 {
+                *arm = (*arm).max(Some(border));
+            }
+        }
+    }
+
+    fn add_box(&mut self, col: Cell, row: Cell, width: Cell, height: Cell, border: usize) {
+        if width < 2 || height < 2 {
+            return
+        }
+
+        let (right, bottom) = (col + width - 1, row + height - 1);
+
+        for c in col..=right {
+            let arms = (if c > col { LEFT } else { 0 }) | (if c < right { RIGHT } else { 0 });
+            self.add(c, row, arms, border);
+            self.add(c, bottom, arms, border);
+        }
+
+        for r in row..=bottom {
+            let arms = (if r > row { UP } else { 0 }) | (if r < bottom { DOWN } else { 0 });
+            self.add(col, r, arms, border);
+            self.add(right, r, arms, border);
+        }
+    }
+
+    fn draw(&self, canvas: &mut (impl Canvas + ?Sized), borders: &[ConnectedBorder]) {
+        let mut cells: Vec<_> = self.cells.iter().collect();
+        cells.sort();
+
+        // cells next to each other on a row, using the same border, are drawn as one run of text
+        let mut run: Option<(Cell, Cell, usize, Str)> = None;
+        let mut next = (0, 0, 0);
+
+        for (&(row, col), arms) in cells {
+            // the latest border decides the style, and the characters if the lines can not be mixed
+            let border = arms.iter().flatten().max().copied().unwrap_or(0);
+
+            if (row, col, border) != next && let Some((col, row, border, text)) = run.take() {
+                canvas.addstr(col, row, &text, borders[border].1);
+            }
+
+            run.get_or_insert_with(|| (col, row, border, Str::new()))
+                .3.push_str(junction(arms, border, borders));
+            next = (row, col + 1, border);
+        }
+
+        if let Some((col, row, border, text)) = run {
+            canvas.addstr(col, row, &text, borders[border].1);
+        }
+    }
+}
+
+/// The character for a cell where lines of possibly different borders meet
+fn junction<'a>(
+        arms: &[Option<usize>; 4],
+        border: usize,
+        borders: &[ConnectedBorder<'a>]) -> &'a str {
+
+    let chars = borders[border].0;
+    let mask = arms.iter().enumerate()
+        .fold(0, |mask, (i, arm)| if arm.is_some() { mask | 1 << i } else { mask });
+
+    if arms.iter().flatten().any(|i| borders[*i].0 != chars) {
+        let mut weights = [0; 4];
+        let mut known = true;
+
+        for (weight, arm) in weights.iter_mut().zip(arms) {
+            if let Some(i) = arm {
+                match box_drawing::weight(borders[*i].0[0]) {
+                    Some(w) => *weight = w,
+                    None => known = false,
+                }
+            }
+        }
+
+        if known && let Some(ch) = box_drawing::from_arms(weights) {
+            return ch
+        }
+    }
+
+    shape(mask, chars)
+}
+
+fn shape(arms: u8, chars: [&str; 11]) -> &str {
+    let [h, v, tl, tr, bl, br, right, left, down, up, cross] = chars;
+
+    match arms {
+        x if x == UP | DOWN | LEFT | RIGHT => cross,
+        x if x == UP | DOWN | RIGHT => right,
+        x if x == UP | DOWN | LEFT => left,
+        x if x == LEFT | RIGHT | DOWN => down,
+        x if x == LEFT | RIGHT | UP => up,
+        x if x == DOWN | RIGHT => tl,
+        x if x == DOWN | LEFT => tr,
+        x if x == UP | RIGHT => bl,
+        x if x == UP | LEFT => br,
+        x if x & (UP | DOWN) != 0 => v,
+        _ => h,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PaneSize::*;
+
+    fn rects(paner: &Paner<u8>, width: Cell, height: Cell, border: &BorderStyle) -> Vec<(u8, Rect)> {
+        paner.render(0, 0, width, height, border).1.into_iter()
+            .map(|(pane, col, row, width, height)| (*pane, (col, row, width, height)))
+            .collect()
+    }
+
+    fn example() -> Paner<u8> {
+        Paner::Horizontal(vec![
+            (Relative(2), Paner::Vertical(vec![(Relative(1), Paner::Pane(0)), (Relative(1), Paner::Pane(1))])),
+            (Relative(3), Paner::Vertical(vec![
+                (Fixed(10), Paner::Horizontal(vec![(Relative(1), Paner::Pane(2)), (Relative(1), Paner::Pane(3))])),
+                (Fixed(1), Paner::Pane(4)),
+                (Relative(1), Paner::Pane(5)),
+                (Relative(1), Paner::Pane(6)),
+            ])),
+            (Relative(1), Paner::Vertical(vec![(Relative(1), Paner::Pane(7)), (Relative(2), Paner::Pane(8))])),
+        ])
+    }
+
+    fn sizes(paner: &Paner<u8>) -> Vec<PaneSize> {
+        match paner {
+            Paner::Horizontal(paners) | Paner::Vertical(paners) => paners.iter().map(|(size, _)| *size).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn split_keeps_the_original_geometry() {
+        let paner = Paner::Horizontal(vec![(Relative(1), Paner::Pane(0)), (Relative(1), Paner::Pane(1))]);
+        assert_eq!(rects(&paner, 80, 24, &BorderStyle::CONNECTED_LIGHT),
+            [(0, (1, 1, 37, 22)), (1, (39, 1, 40, 22))]);
+
+        assert_eq!(rects(&example(), 80, 24, &BorderStyle::CONNECTED_LIGHT), [
+            (0, (1, 1, 24, 9)), (1, (1, 11, 24, 12)), (2, (26, 1, 16, 10)), (3, (43, 1, 20, 10)),
+            (4, (26, 12, 37, 1)), (5, (26, 14, 37, 3)), (6, (26, 18, 37, 5)), (7, (64, 1, 15, 5)),
+            (8, (64, 7, 15, 16)),
+        ]);
+
+        assert_eq!(rects(&example(), 80, 24, &BorderStyle::DISCONNECTED_LIGHT), [
+            (0, (1, 1, 23, 8)), (1, (1, 11, 23, 12)), (2, (26, 1, 15, 10)), (3, (43, 1, 19, 10)),
+            (4, (26, 13, 36, 1)), (5, (26, 16, 36, 2)), (6, (26, 20, 36, 3)), (7, (64, 1, 15, 4)),
+            (8, (64, 7, 15, 16)),
+        ]);
+    }
+
+    #[test]
+    fn split_fits_fixed_sizes_first() {
+        let sizes = [(Fixed(10), Paner::Pane(0)), (Relative(3), Paner::Pane(1)),
+            (Relative(5), Paner::Pane(2)), (Fixed(4), Paner::Pane(3))];
+        assert_eq!(split(&sizes, 78, 1), [10, 22, 38, 4]);
+
+        // fixed sizes larger than the space are cut off
+        assert_eq!(split(&[(Fixed(50), Paner::Pane(0)), (Fixed(50), Paner::Pane(1))], 60, 1), [50, 9]);
+        assert_eq!(split(&[(Relative(0), Paner::Pane(0))], 10, 1), [10]);
+    }
+
+    #[test]
+    fn tabs_are_placed_by_display_width() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let paner: Paner<u8> = "t[*漢字:0,b:1]".parse().unwrap();
+
+        let Paner::Tabs { tabs, .. } = &paner else { panic!() };
+        assert_eq!(tab_positions(tabs, 1, 12), [(2, " 漢字 ".to_string()), (9, " b ".to_string())]);
+        assert_eq!(paner.find_tab(8, 0, 14, 3, &border), None);
+        assert_eq!(paner.find_tab(9, 0, 14, 3, &border), Some((vec![], 1)));
+    }
+
+    #[test]
+    fn labels_are_aligned_by_display_width() {
+        let mut canvas = InstructionBuffer::new(14, 2, None);
+        draw_label(&mut canvas, &Label::new("漢字").align(Align::Right), 0, 0, 14);
+        draw_label(&mut canvas, &Label::new("end").align(Align::Center), 0, 1, 14);
+
+        // wide graphemes take up two columns when placing labels
+        let chars = canvas.render_to_chars();
+        let ch = |col, row| chars.get(&(col, row)).map(|(ch, ..)| *ch);
+        assert_eq!((ch(7, 0), ch(8, 0), ch(9, 0)), (Some(' '), Some('漢'), Some('字')));
+        assert_eq!((ch(4, 1), ch(5, 1)), (Some(' '), Some('e')));
+    }
+
+    #[test]
+    fn focus_moves_to_neighbours() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let paner = Paner::Horizontal(vec![
+            (Relative(1), Paner::Vertical(vec![(Relative(1), Paner::Pane(0)), (Relative(1), Paner::Pane(1))])),
+            (Relative(1), Paner::Vertical(vec![(Relative(1), Paner::Pane(2)), (Relative(2), Paner::Pane(3))])),
+        ]);
+        let mut focus = Focus::new();
+
+        assert!(!focus.go(&paner, Direction::Left, 40, 12, &border));
+        assert!(focus.go(&paner, Direction::Down, 40, 12, &border));
+        assert_eq!(focus.index(), 1);
+        // the pane overlapping the most is preferred
+        assert!(focus.go(&paner, Direction::Right, 40, 12, &border));
+        assert_eq!(focus.index(), 3);
+        assert!(focus.go(&paner, Direction::Up, 40, 12, &border));
+        assert_eq!(focus.index(), 2);
+        assert!(focus.go(&paner, Direction::Left, 40, 12, &border));
+        assert_eq!(focus.index(), 0);
+
+        assert_eq!(Direction::from_ctrl_arrow(CTRL_ARROW_UP), Some(Direction::Up));
+        assert_eq!(Direction::from_ctrl_arrow(ARROW_UP), None);
+    }
+
+    #[test]
+    fn framed_borders_replace_the_default() {
+        let frame = Frame::new().border(BorderStyle::CONNECTED_DOUBLE).style(Style::new().bold());
+        let paner = Paner::Horizontal(vec![
+            (Relative(1), Paner::Pane(0)),
+            (Relative(1), Paner::Pane(1).framed(frame)),
+            (Relative(1), Paner::Pane(2)),
+        ]);
+        let (rendered, _) = paner.render(0, 0, 16, 3, &BorderStyle::CONNECTED_LIGHT);
+
+        // the lines of the framed pane are drawn in its style, joining the lighter ones next to it
+        let bold = Style::new().bold().as_string();
+        assert!(rendered.contains(&format!("{bold}╦══╦")));
+        assert!(rendered.contains(&format!("{bold}╩══╩")));
+        assert!(rendered.contains(&format!("{}────────┐", Style::EMPTY.as_string())));
+    }
+
+    #[test]
+    fn swap_panes() {
+        let mut paner = example();
+        assert!(paner.swap(0, 8));
+        assert_eq!(paner.get(0), Some(&8));
+        assert_eq!(paner.get(8), Some(&0));
+
+        // swapping a pane with itself keeps it
+        assert!(paner.swap(3, 3));
+        assert_eq!(paner.panes(), [&8, &1, &2, &3, &4, &5, &6, &7, &0]);
+
+        assert!(!paner.swap(0, 9));
+    }
+
+    #[test]
+    fn resize_panes() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let mut paner = Paner::Horizontal(vec![(Relative(1), Paner::Pane(0)), (Relative(1), Paner::Pane(1))]);
+
+        assert!(paner.resize(0, Direction::Right, 5, 80, 24, &border));
+        assert_eq!(rects(&paner, 80, 24, &border), [(0, (1, 1, 42, 22)), (1, (44, 1, 35, 22))]);
+
+        assert!(paner.resize(1, Direction::Left, -1, 80, 24, &border));
+        assert_eq!(rects(&paner, 80, 24, &border), [(0, (1, 1, 41, 22)), (1, (43, 1, 36, 22))]);
+
+        // panes do not shrink below one cell
+        assert!(paner.resize(0, Direction::Right, 1000, 80, 24, &border));
+        assert_eq!(rects(&paner, 80, 24, &border), [(0, (1, 1, 76, 22)), (1, (78, 1, 1, 22))]);
+        assert!(!paner.resize(0, Direction::Right, 1, 80, 24, &border));
+
+        // the outer edges can not be moved
+        assert!(!paner.resize(0, Direction::Left, 1, 80, 24, &border));
+        assert!(!paner.resize(0, Direction::Down, 1, 80, 24, &border));
+    }
+
+    #[test]
+    fn resize_fixed_panes() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let mut paner = Paner::Vertical(vec![
+            (Relative(1), Paner::Pane(0)),
+            (Relative(1), Paner::Pane(1)),
+            (Fixed(1), Paner::Pane(2)),
+        ]);
+
+        assert!(paner.resize(1, Direction::Up, -2, 80, 24, &border));
+        assert_eq!(rects(&paner, 80, 24, &border)[..2], [(0, (1, 1, 78, 6)), (1, (1, 8, 78, 10))]);
+
+        assert!(paner.resize(2, Direction::Up, -3, 80, 24, &border));
+        assert_eq!(sizes(&paner)[2], Fixed(4));
+
+        // a pane without any room can not be moved into
+        let mut paner = Paner::Horizontal(vec![(Fixed(0), Paner::Pane(0)), (Relative(1), Paner::Pane(1))]);
+        assert!(!paner.resize(1, Direction::Left, -3, 80, 24, &border));
+        assert!(!paner.resize(0, Direction::Right, -1, 80, 24, &border));
+        assert!(paner.resize(1, Direction::Left, 3, 80, 24, &border));
+        assert_eq!(sizes(&paner)[0], Fixed(3));
+    }
+
+    #[test]
+    fn split_and_close_keep_other_sizes() {
+        let mut paner = example();
+        assert_eq!(paner.split(8, Direction::Right, 9), Some(9));
+        assert_eq!(paner.panes(), [&0, &1, &2, &3, &4, &5, &6, &7, &8, &9]);
+        assert_eq!(sizes(&paner), [Relative(2), Relative(3), Relative(1)]);
+
+        assert_eq!(paner.close(9), Some(9));
+        assert_eq!(sizes(&paner), [Relative(2), Relative(3), Relative(1)]);
+        assert_eq!(rects(&paner, 80, 24, &BorderStyle::CONNECTED_LIGHT), rects(&example(), 80, 24, &BorderStyle::CONNECTED_LIGHT));
+
+        // closing a pane collapses its parent if it has a single child left
+        assert_eq!(paner.close(0), Some(0));
+        let Paner::Horizontal(paners) = &paner else { panic!() };
+        assert!(matches!(paners[0], (Relative(2), Paner::Pane(1))));
+
+        let mut paner = Paner::Pane(0);
+        assert_eq!(paner.close(0), None);
+    }
+
+    #[test]
+    fn normalize_merges_and_reduces() {
+        let mut paner = Paner::Horizontal(vec![
+            (Relative(2), Paner::Horizontal(vec![(Relative(1), Paner::Pane(0)), (Relative(1), Paner::Pane(1))])),
+            (Relative(4), Paner::Vertical(vec![(Relative(3), Paner::Pane(2))])),
+            (Relative(6), Paner::Vertical(Vec::new())),
+        ]);
+
+        paner.normalize();
+        assert_eq!(sizes(&paner), [Relative(1), Relative(1), Relative(4)]);
+        assert_eq!(paner.panes(), [&0, &1, &2]);
+        let Paner::Horizontal(paners) = &paner else { panic!() };
+        assert!(matches!(paners[2].1, Paner::Pane(2)));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let text = r##"h[2:v[1:a,=3:"b c"],1:f[title=Logs,footer="3 errors",footer-align=right,footer-style="bold red",border="connected:-|+++++++++",style="#ff8000 on 208":t[*logs:d,"say \"hi\"":"e\\f"]],1:s[fill:g,20x5+1+2:f[:h]]]"##;
+
+        let paner: Paner<Str> = text.parse().unwrap();
+        assert_eq!(paner.to_string(), text);
+        assert_eq!(paner.panes(), ["a", "b c", "d", "e\\f", "g", "h"]);
+
+        let Paner::Horizontal(paners) = &paner else { panic!() };
+        let Paner::Framed(frame, _) = &paners[1].1 else { panic!() };
+        let footer = frame.footer.as_ref().unwrap();
+        assert_eq!(footer.text, "3 errors");
+        assert!(footer.align == Align::Right && footer.style == Some(Style::new().bold().fg(Color::Red)));
+        assert!(frame.border == Some(BorderStyle::connected_from_str("-|+++++++++")));
+        assert!(frame.style == Some(Style::new().fg(Color::True(255, 128, 0)).bg(Color::Ansi(208))));
+
+        let Paner::Tabs { tabs, .. } = &frame_child(&paners[1].1) else { panic!() };
+        assert_eq!(tabs[1].0, "say \"hi\"");
+        assert_eq!(tabs[1].1.to_string(), r#""e\\f""#);
+
+        // frames with nothing set are kept
+        let paner: Paner<Str> = "f[:a]".parse().unwrap();
+        assert!(matches!(paner, Paner::Framed(..)));
+        assert_eq!(paner.to_string(), "f[:a]");
+
+        assert!("f[titel=a:b]".parse::<Paner<Str>>().is_err());
+        assert!("f[title=a]".parse::<Paner<Str>>().is_err());
+        assert!("h[1:a,b]".parse::<Paner<Str>>().is_err());
+
+        assert!(matches!("20x8".parse::<Float>(), Ok(Float::Centered(20, 8))));
+        assert!(matches!("20x8+2+1".parse::<Float>(), Ok(Float::At(2, 1, 20, 8))));
+        for float in ["20+8", "20x8x2x1", "20+8x2+1", "20x8+2", "20x8+2+1+3", "20x+8"] {
+            assert!(float.parse::<Float>().is_err(), "{float}");
+        }
+    }
+
+    fn frame_child(paner: &Paner<Str>) -> &Paner<Str> {
+        match paner {
+            Paner::Framed(_, paner) => paner,
+            _ => paner,
+        }
+    }
+
+    #[test]
+    fn indices_include_hidden_tabs() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let mut paner: Paner<u8> = "h[1:0,1:t[*one:v[1:1,1:2],two:3],1:4]".parse().unwrap();
+
+        assert_eq!(paner.panes(), [&0, &1, &2, &3, &4]);
+        assert_eq!(paner.visible(), [0, 1, 2, 4]);
+        assert_eq!(paner.get(3), Some(&3));
+        assert_eq!(rects(&paner, 80, 24, &border).iter().map(|(pane, _)| *pane).collect::<Vec<_>>(), [0, 1, 2, 4]);
+
+        // switching tabs keeps the indices, and moves the focus to a shown pane
+        let mut focus = Focus::new();
+        focus.set(&paner, 2);
+        assert!(paner.select_tab(1, 1));
+        assert_eq!(paner.visible(), [0, 3, 4]);
+        assert_eq!(paner.get(4), Some(&4));
+        assert_eq!(paner.rects(80, 24, &border).iter().map(|(index, _)| *index).collect::<Vec<_>>(), [0, 3, 4]);
+
+        focus.clamp(&paner);
+        assert_eq!(focus.index(), 3);
+        focus.next(&paner);
+        assert_eq!(focus.index(), 4);
+        focus.next(&paner);
+        assert_eq!(focus.index(), 0);
+        focus.previous(&paner);
+        assert_eq!(focus.index(), 4);
+        assert!(focus.go(&paner, Direction::Left, 80, 24, &border));
+        assert_eq!(focus.index(), 3);
+
+        // panes in hidden tabs can be split and closed
+        assert_eq!(paner.split(1, Direction::Down, 5), Some(2));
+        assert_eq!(paner.panes(), [&0, &1, &5, &2, &3, &4]);
+        assert_eq!(paner.close(3), Some(2));
+        assert_eq!(paner.close(3), Some(3));
+        assert_eq!(paner.to_string(), "h[1:0,1:t[*one:v[1:1,1:5]],1:4]");
+    }
+
+    /// Remembers the last event it was given
+    #[derive(Default)]
+    struct Recorder(Option<Event>);
+
+    impl Widget for Recorder {
+        fn render(&self, _area: Area, _canvas: &mut dyn Canvas) {}
+
+        fn handle_event(&mut self, event: Event) -> EventResult {
+            self.0 = Some(event);
+            EventResult::Consumed
+        }
+    }
+
+    #[test]
+    fn mouse_events_are_relative_to_the_area() {
+        let border = BorderStyle::CONNECTED_LIGHT;
+        let mut paner = Paner::Horizontal(vec![
+            (Relative(1), Paner::Pane(Recorder::default())),
+            (Relative(1), Paner::Pane(Recorder::default())),
+        ]);
+        let mut focus = Focus::new();
+        let area = (10, 5, 80, 24);
+        let click = |col, row| Event::Mouse { button: 0, col, row, pressed: true };
+
+        // the second pane starts at column 39 of the layout
+        assert_eq!(paner.handle_event(&mut focus, click(50, 6), area, &border), EventResult::Consumed);
+        assert_eq!(focus.index(), 1);
+        assert_eq!(paner.get(1).unwrap().0, Some(Event::Mouse { button: 0, col: 1, row: 0, pressed: true }));
+
+        assert_eq!(paner.handle_event(&mut focus, click(11, 7), area, &border), EventResult::Consumed);
+        assert_eq!(focus.index(), 0);
+        assert_eq!(paner.get(0).unwrap().0, Some(Event::Mouse { button: 0, col: 0, row: 1, pressed: true }));
+
+        // outside of the layout, and on its border
+        assert_eq!(paner.handle_event(&mut focus, click(5, 6), area, &border), EventResult::Ignored);
+        assert_eq!(paner.handle_event(&mut focus, click(10, 5), area, &border), EventResult::Ignored);
+
+        assert_eq!(paner.handle_event(&mut focus, Event::Key(b"a".to_vec()), area, &border), EventResult::Consumed);
+        assert_eq!(paner.get(0).unwrap().0, Some(Event::Key(b"a".to_vec())));
+    }
+}
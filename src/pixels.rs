@@ -0,0 +1,292 @@
+use crate::*;
+
+/// How pixels are put into cells
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PixelKind {
+    /// 2x4 dots per cell, sharing one color
+    Braille,
+    /// 1x2 pixels per cell, each with its own color
+    HalfBlock,
+}
+
+impl PixelKind {
+    /// Width and height of the pixels in a single cell
+    pub const fn cell_size(self) -> (Cell, Cell) {
+        match self {
+            Self::Braille => (2, 4),
+            Self::HalfBlock => (1, 2),
+        }
+    }
+}
+
+/// The bit of a braille pattern for every dot, by row and column
+const BRAILLE_DOTS: [[u8; 2]; 4] = [
+    [0x01, 0x08],
+    [0x02, 0x10],
+    [0x04, 0x20],
+    [0x40, 0x80],
+];
+
+/// Pixels smaller than a cell, drawn onto a `Canvas` with `PixelCanvas::blit`.
+/// Shapes may go outside of the canvas, and are clipped.
+#[derive(Clone)]
+pub struct PixelCanvas {
+    kind: PixelKind,
+    pixels: Vec<Option<Color>>,
+    width: Cell,
+    height: Cell,
+}
+
+impl PixelCanvas {
+    /// A canvas covering some amount of cells
+    pub fn new(kind: PixelKind, cols: Cell, rows: Cell) -> Self {
+        let (cell_width, cell_height) = kind.cell_size();
+        let (width, height) = (cols.saturating_mul(cell_width), rows.saturating_mul(cell_height));
+
+        Self {
+            kind,
+            pixels: vec![None; width as usize * height as usize],
+            width,
+            height,
+        }
+    }
+
+    pub fn braille(cols: Cell, rows: Cell) -> Self {
+        Self::new(PixelKind::Braille, cols, rows)
+    }
+
+    pub fn half_block(cols: Cell, rows: Cell) -> Self {
+        Self::new(PixelKind::HalfBlock, cols, rows)
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> Cell {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> Cell {
+        self.height
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(None);
+    }
+
+    pub fn get(&self, x: Cell, y: Cell) -> Option<&Color> {
+        self.index(x as i32, y as i32).and_then(|i| self.pixels[i].as_ref())
+    }
+
+    pub fn point(&mut self, x: Cell, y: Cell, color: Color) {
+        self.set(x as i32, y as i32, Some(color));
+    }
+
+    pub fn erase(&mut self, x: Cell, y: Cell) {
+        self.set(x as i32, y as i32, None);
+    }
+
+    pub fn line(&mut self, start: (Cell, Cell), end: (Cell, Cell), color: Color) {
+        let (mut x, mut y) = (start.0 as i32, start.1 as i32);
+        let (end_x, end_y) = (end.0 as i32, end.1 as i32);
+
+        let delta_x = (end_x - x).abs();
+        let delta_y = -(end_y - y).abs();
+        let step_x = if x < end_x { 1 } else { -1 };
+        let step_y = if y < end_y { 1 } else { -1 };
+        let mut error = delta_x + delta_y;
+
+        loop {
+            self.set(x, y, Some(color.clone()));
+
+            if x == end_x && y == end_y {
+                break
+            }
+
+            let double = error * 2;
+            if double >= delta_y {
+                error += delta_y;
+                x += step_x;
+            }
+            if double <= delta_x {
+                error += delta_x;
+                y += step_y;
+            }
+        }
+    }
+
+    /// The outline of a rectangle
+    pub fn rect(&mut self, x: Cell, y: Cell, width: Cell, height: Cell, color: Color) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        let (right, bottom) = (x.saturating_add(width - 1), y.saturating_add(height - 1));
+        self.line((x, y), (right, y), color.clone());
+        self.line((x, bottom), (right, bottom), color.clone());
+        self.line((x, y), (x, bottom), color.clone());
+        self.line((right, y), (right, bottom), color);
+    }
+
+    pub fn fill_rect(&mut self, x: Cell, y: Cell, width: Cell, height: Cell, color: Color) {
+        for y in y..y.saturating_add(height) {
+            for x in x..x.saturating_add(width) {
+                self.set(x as i32, y as i32, Some(color.clone()));
+            }
+        }
+    }
+
+    /// The outline of a circle around a center
+    pub fn circle(&mut self, x: Cell, y: Cell, radius: Cell, color: Color) {
+        let (center_x, center_y) = (x as i32, y as i32);
+        let (mut x, mut y) = (radius as i32, 0);
+        let mut error = 1 - x;
+
+        while x >= y {
+            for (x, y) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.set(center_x + x, center_y + y, Some(color.clone()));
+            }
+
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        (x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32)
+            .then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: Option<Color>) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = color;
+        }
+    }
+
+    /// The character and style of a cell, or `None` if it has no pixels
+    fn cell(&self, col: Cell, row: Cell) -> Option<(Str, Style)> {
+        let (cell_width, cell_height) = self.kind.cell_size();
+        let pixel = |x, y| self.get(col * cell_width + x, row * cell_height + y);
+
+        match self.kind {
+            PixelKind::Braille => {
+                let mut pattern = 0;
+                let mut color = None;
+
+                for (y, dots) in BRAILLE_DOTS.iter().enumerate() {
+                    for (x, dot) in dots.iter().enumerate() {
+                        if let Some(pixel) = pixel(x as Cell, y as Cell) {
+                            pattern |= dot;
+                            color = Some(pixel);
+                        }
+                    }
+                }
+
+                let ch = char::from_u32(0x2800 + pattern as u32)?;
+                color.map(|color| (ch.to_string(), Style::new().fg(color.clone())))
+            }
+            PixelKind::HalfBlock => match (pixel(0, 0), pixel(0, 1)) {
+                (Some(top), Some(bottom)) =>
+                    Some(("▀".to_string(), Style::new().fg(top.clone()).bg(bottom.clone()))),
+                (Some(top), None) => Some(("▀".to_string(), Style::new().fg(top.clone()))),
+                (None, Some(bottom)) => Some(("▄".to_string(), Style::new().fg(bottom.clone()))),
+                (None, None) => None,
+            },
+        }
+    }
+
+    /// Draw the pixels onto a canvas, leaving cells without any pixels untouched
+    pub fn blit(&self, canvas: &mut (impl Canvas + ?Sized), col: Cell, row: Cell) {
+        let (cell_width, cell_height) = self.kind.cell_size();
+        let cols = self.width / cell_width;
+        let rows = self.height / cell_height;
+
+        for i in 0..rows {
+            // draw runs of cells with the same style together
+            let mut run: Option<(Cell, Str, Style)> = None;
+
+            for j in 0..=cols {
+                let cell = if j < cols { self.cell(j, i) } else { None };
+
+                match (&mut run, cell) {
+                    (Some((_, text, style)), Some((ch, new_style))) if *style == new_style => {
+                        text.push_str(&ch);
+                    }
+                    (_, cell) => {
+                        if let Some((start, text, style)) = run.take() {
+                            canvas.addstr(col + start, row + i, &text, Some(&style));
+                        }
+                        run = cell.map(|(ch, style)| (j, ch, style));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(pixels: &PixelCanvas) -> Vec<Option<Str>> {
+        let mut canvas = InstructionBuffer::new(pixels.width(), pixels.height(), None);
+        pixels.blit(&mut canvas, 0, 0);
+        canvas.rasterize().into_iter().map(|cell| cell.map(|(ch, _)| ch)).collect()
+    }
+
+    #[test]
+    fn braille_dots() {
+        let mut pixels = PixelCanvas::braille(2, 1);
+        assert_eq!((pixels.width(), pixels.height()), (4, 4));
+
+        pixels.point(0, 0, Color::Red);
+        pixels.point(1, 3, Color::Red);
+        pixels.line((2, 0), (2, 3), Color::Red);
+        assert_eq!(cells(&pixels)[..2], [Some("⢁".to_string()), Some("⡇".to_string())]);
+
+        pixels.erase(0, 0);
+        pixels.erase(1, 3);
+        assert_eq!(cells(&pixels)[..2], [None, Some("⡇".to_string())]);
+    }
+
+    #[test]
+    fn half_blocks() {
+        let mut pixels = PixelCanvas::half_block(3, 1);
+        pixels.point(0, 0, Color::Red);
+        pixels.point(1, 1, Color::Blue);
+        pixels.fill_rect(2, 0, 1, 2, Color::Green);
+
+        let mut canvas = InstructionBuffer::new(3, 1, None);
+        pixels.blit(&mut canvas, 0, 0);
+        let cells = canvas.rasterize();
+
+        assert!(cells[0] == Some(("▀".to_string(), Style::new().fg(Color::Red))));
+        assert!(cells[1] == Some(("▄".to_string(), Style::new().fg(Color::Blue))));
+        assert!(cells[2] == Some(("▀".to_string(), Style::new().fg(Color::Green).bg(Color::Green))));
+    }
+
+    #[test]
+    fn shapes_are_clipped() {
+        let mut pixels = PixelCanvas::half_block(4, 2);
+        pixels.circle(0, 0, 2, Color::Red);
+        pixels.rect(2, 2, 10, 10, Color::Red);
+
+        assert!(pixels.get(2, 0).is_some());
+        assert!(pixels.get(0, 2).is_some());
+        assert!(pixels.get(1, 1).is_none());
+        assert!(pixels.get(3, 2).is_some());
+        assert!(pixels.get(2, 3).is_some());
+        assert!(pixels.get(3, 3).is_none());
+        assert!(pixels.get(4, 0).is_none());
+
+        // sizes saturate instead of overflowing
+        pixels.rect(Cell::MAX, Cell::MAX, Cell::MAX, Cell::MAX, Color::Red);
+        pixels.rect(0, 0, 0, 2, Color::Blue);
+        assert!(pixels.get(0, 0).is_none());
+        assert_eq!(PixelCanvas::braille(Cell::MAX, 1).width(), Cell::MAX);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::*;
+use crate::charts::HORIZONTAL_BLOCKS;
+use std::io::IsTerminal;
+
+/// Something shown on a line of its own by `Progress`
+pub trait Indicator {
+    /// The line to draw, which may contain escape sequences but must fit in the width
+    fn line(&self, width: Cell) -> Str;
+
+    /// The line logged when stdout is not a terminal.
+    /// A line is only logged again when it changes.
+    fn plain(&self) -> Str;
+}
+
+/// How much of some amount of work is done
+pub struct ProgressBar {
+    position: u64,
+    total: u64,
+    message: Str,
+    style: Option<Style>,
+}
+
+impl ProgressBar {
+    pub fn new(total: u64) -> Self {
+        Self {
+            position: 0,
+            total,
+            message: Str::new(),
+            style: None,
+        }
+    }
+
+    /// Shown before the bar
+    pub fn message(mut self, message: impl Into<Str>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Style of the filled part of the bar
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn set_message(&mut self, message: impl Into<Str>) {
+        self.message = message.into();
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position.min(self.total);
+    }
+
+    pub fn inc(&mut self, amount: u64) {
+        self.set_position(self.position.saturating_add(amount));
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.total
+    }
+
+    fn fraction(&self) -> f64 {
+        if self.total == 0 { 1.0 } else { self.position as f64 / self.total as f64 }
+    }
+}
+
+impl Indicator for ProgressBar {
+    fn line(&self, width: Cell) -> Str {
+        let percent = format!(" {:>3}%", (self.fraction() * 100.0) as u64);
+        let message = match self.message.is_empty() {
+            true => Str::new(),
+            false => utils::truncate(&self.message, width / 3) + " ",
+        };
+
+        let used = utils::display_width(&message) + percent.len();
+        let length = (width as usize).saturating_sub(used);
+        let eighths = (self.fraction() * length as f64 * 8.0).round() as usize;
+        let (full, partial) = (eighths / 8, eighths % 8);
+
+        let mut bar = HORIZONTAL_BLOCKS[8].repeat(full);
+        if partial > 0 {
+            bar.push_str(HORIZONTAL_BLOCKS[partial]);
+        }
+        let empty = " ".repeat(length.saturating_sub(full + (partial > 0) as usize));
+
+        match &self.style {
+            Some(style) => format!("{message}{style}{bar}{RESET}{empty}{percent}"),
+            None => format!("{message}{bar}{empty}{percent}"),
+        }
+    }
+
+    fn plain(&self) -> Str {
+        // only log every tenth
+        let percent = (self.fraction() * 10.0) as u64 * 10;
+        match self.message.is_empty() {
+            true => format!("{percent}%"),
+            false => format!("{} {percent}%", self.message),
+        }
+    }
+}
+
+/// Shows that something is happening, without knowing how much is done
+pub struct Spinner {
+    frame: usize,
+    frames: &'static [Static],
+    message: Str,
+    done: bool,
+    style: Option<Style>,
+}
+
+impl Spinner {
+    pub const DOTS: &'static [Static] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    pub const LINE: &'static [Static] = &["-", "\\", "|", "/"];
+
+    /// Shown when done
+    pub const DONE: Static = "✓";
+
+    pub fn new(message: impl Into<Str>) -> Self {
+        Self {
+            frame: 0,
+            frames: Self::DOTS,
+            message: message.into(),
+            done: false,
+            style: None,
+        }
+    }
+
+    pub fn frames(mut self, frames: &'static [Static]) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Style of the spinner
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn set_message(&mut self, message: impl Into<Str>) {
+        self.message = message.into();
+    }
+
+    /// Advance to the next frame
+    pub fn tick(&mut self) {
+        self.frame = (self.frame + 1) % self.frames.len().max(1);
+    }
+
+    /// Show `DONE` instead of the frames
+    pub fn finish(&mut self) {
+        self.done = true;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done
+    }
+}
+
+impl Indicator for Spinner {
+    fn line(&self, width: Cell) -> Str {
+        let symbol = match self.done {
+            true => Self::DONE,
+            false => self.frames.get(self.frame).copied().unwrap_or_default(),
+        };
+        let message = utils::truncate(&self.message, width.saturating_sub(2));
+
+        match &self.style {
+            Some(style) => format!("{style}{symbol}{RESET} {message}"),
+            None => format!("{symbol} {message}"),
+        }
+    }
+
+    fn plain(&self) -> Str {
+        match self.done {
+            true => format!("{} done", self.message),
+            false => self.message.clone(),
+        }
+    }
+}
+
+/// Draws indicators on the lines below the cursor, redrawing them in place.
+/// When stdout is not a terminal, changed indicators are logged as plain lines instead.
+///
+/// Nothing else should be printed between drawing and `finish`.
+pub struct Progress {
+    width: Cell,
+    terminal: bool,
+    /// How many lines were drawn last time
+    lines: usize,
+    /// What was last logged for every indicator
+    logged: Vec<Str>,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self {
+            width: 80,
+            terminal: std::io::stdout().is_terminal(),
+            lines: 0,
+            logged: Vec::new(),
+        }
+    }
+
+    /// Width of the terminal, which lines are kept within so that they do not wrap
+    pub fn width(mut self, width: Cell) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Draw or log indicators, one per line, and flush stdout
+    pub fn draw(&mut self, indicators: &[&dyn Indicator]) {
+        if !self.terminal {
+            self.log(indicators);
+            return
+        }
+
+        let mut output = Str::new();
+        if self.lines > 1 {
+            output.push_str(&formatf!("{CUR_UP}", self.lines - 1));
+        }
+
+        // leave the last column empty, as some terminals wrap when it is written to
+        let width = self.width.saturating_sub(1);
+        for (i, indicator) in indicators.iter().enumerate() {
+            if i > 0 {
+                output.push('\n');
+            }
+            output.push_str(CUR_COL_HOME);
+            output.push_str(ERASE_LINE);
+            output.push_str(&indicator.line(width));
+        }
+
+        // remove lines left over from drawing more indicators
+        output.push_str(ERASE_TO_END);
+        print!("{output}");
+        utils::flush();
+
+        self.lines = indicators.len();
+    }
+
+    /// Draw indicators one last time and move below them, so that they are left as they are
+    pub fn finish(&mut self, indicators: &[&dyn Indicator]) {
+        self.draw(indicators);
+        if self.terminal && self.lines > 0 {
+            println!();
+        }
+        self.lines = 0;
+        self.logged.clear();
+    }
+
+    fn log(&mut self, indicators: &[&dyn Indicator]) {
+        self.logged.resize(indicators.len(), Str::new());
+
+        for (indicator, logged) in indicators.iter().zip(&mut self.logged) {
+            let plain = indicator.plain();
+            if plain != *logged {
+                println!("{plain}");
+                *logged = plain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_fill_the_width() {
+        let mut bar = ProgressBar::new(8).message("漢字");
+        bar.inc(3);
+        assert_eq!(bar.line(20), "漢字 ███▊        37%");
+
+        bar.inc(10);
+        assert!(bar.is_finished());
+        let styled = bar.style(Style::new().bold()).line(20);
+        assert_eq!(utils::visible_width(&styled), 20);
+
+        assert_eq!(ProgressBar::new(0).line(6), "█ 100%");
+    }
+
+    #[test]
+    fn bars_log_every_tenth() {
+        let mut bar = ProgressBar::new(100).message("copying");
+        bar.set_position(19);
+        assert_eq!(bar.plain(), "copying 10%");
+        bar.set_position(150);
+        assert_eq!(bar.position(), 100);
+        assert_eq!(bar.plain(), "copying 100%");
+    }
+
+    #[test]
+    fn spinner_frames() {
+        let mut spinner = Spinner::new("loading").frames(Spinner::LINE);
+        spinner.tick();
+        assert_eq!(spinner.line(10), "\\ loading");
+        (0..3).for_each(|_| spinner.tick());
+        assert_eq!(spinner.line(6), "- loa…");
+
+        spinner.finish();
+        assert_eq!(spinner.line(10), "✓ loading");
+        assert_eq!(spinner.plain(), "loading done");
+    }
+}
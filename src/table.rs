@@ -0,0 +1,406 @@
+use crate::*;
+use crate::input_sequences::*;
+use std::cmp::Ordering;
+
+/// A column of a `Table`
+#[derive(Clone, PartialEq)]
+pub struct Column {
+    pub title: Str,
+    /// Fixed, or a share of the width left over by the fixed columns
+    pub width: PaneSize,
+    pub align: Align,
+}
+
+impl Column {
+    pub fn new(title: impl Into<Str>, width: PaneSize) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            align: Align::Left,
+        }
+    }
+
+    /// Alignment of the title and the cells
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+/// Compare cells as numbers if both are numbers, otherwise as text
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Divide a width by column sizes, leaving a gap between each of them
+fn split_sizes(sizes: &[PaneSize], length: Cell, gap: Cell) -> Vec<Cell> {
+    let gaps = gap.saturating_mul(sizes.len().saturating_sub(1) as Cell);
+    let mut remaining = length.saturating_sub(gaps);
+
+    // fixed sizes are taken first, in order
+    let mut lengths: Vec<Cell> = sizes.iter()
+        .map(|size| match size {
+            PaneSize::Fixed(size) => {
+                let size = (*size).min(remaining);
+                remaining -= size;
+                size
+            }
+            PaneSize::Relative(..) => 0,
+        })
+        .collect();
+
+    let total_rel: u32 = sizes.iter()
+        .map(|i| if let PaneSize::Relative(size) = i { *size as u32 } else { 0 }).sum();
+
+    if total_rel == 0 {
+        return lengths
+    }
+
+    let space = remaining as u32;
+    for (length, size) in lengths.iter_mut().zip(sizes) {
+        if let PaneSize::Relative(rel) = size {
+            *length = (space * *rel as u32 / total_rel) as Cell;
+            remaining -= *length;
+        }
+    }
+
+    // hand out what was lost to rounding, one cell each
+    for (length, size) in lengths.iter_mut().zip(sizes) {
+        if remaining == 0 {
+            break
+        }
+        if matches!(size, PaneSize::Relative(rel) if *rel > 0) {
+            *length += 1;
+            remaining -= 1;
+        }
+    }
+
+    lengths
+}
+
+/// Rows of cells below a header, one of which is selected.
+///
+/// Moved through like a `List`. Clicking the title of a column sorts the rows by it, and
+/// clicking it again reverses the order.
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Str>>,
+    /// Draws the line below the header and between columns, a gap leaves empty space instead
+    border: BorderStyle,
+    selected: usize,
+    /// The column sorted by, and whether the order is ascending
+    sort: Option<(usize, bool)>,
+    /// The first row shown, updated when rendering
+    scroll: std::cell::Cell<usize>,
+    /// The width and height of the rows when last rendered, for pages and mouse events
+    size: std::cell::Cell<(Cell, Cell)>,
+    style: Option<Style>,
+    header_style: Style,
+    highlight_style: Style,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>, rows: Vec<Vec<Str>>) -> Self {
+        Self {
+            columns,
+            rows,
+            border: BorderStyle::CONNECTED_LIGHT,
+            selected: 0,
+            sort: None,
+            scroll: std::cell::Cell::new(0),
+            size: std::cell::Cell::new((0, 1)),
+            style: None,
+            header_style: Style::new().bold(),
+            highlight_style: Style::new().fg(Color::Black).bg(Color::White),
+        }
+    }
+
+    pub fn border(mut self, border: BorderStyle) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Style of the cells and separators
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Style of the selected row
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn rows(&self) -> &[Vec<Str>] {
+        &self.rows
+    }
+
+    /// Replace the rows, keeping them sorted and the selection within them
+    pub fn set_rows(&mut self, rows: Vec<Vec<Str>>) {
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+        if let Some((column, ascending)) = self.sort {
+            self.sort_by(column, ascending);
+        }
+    }
+
+    /// Index of the selected row, unless there are no rows
+    pub fn selected(&self) -> Option<usize> {
+        (!self.rows.is_empty()).then_some(self.selected)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// The column sorted by, and whether the order is ascending
+    pub fn sorted(&self) -> Option<(usize, bool)> {
+        self.sort
+    }
+
+    /// Sort the rows by a column, keeping the same row selected
+    pub fn sort_by(&mut self, column: usize, ascending: bool) {
+        if column >= self.columns.len() {
+            return
+        }
+
+        let mut rows: Vec<_> = std::mem::take(&mut self.rows).into_iter().enumerate().collect();
+        rows.sort_by(|(_, a), (_, b)| {
+            let ordering = compare_cells(a.get(column).map_or("", |cell| cell), b.get(column).map_or("", |cell| cell));
+            if ascending { ordering } else { ordering.reverse() }
+        });
+
+        self.selected = rows.iter().position(|(i, _)| *i == self.selected).unwrap_or(0);
+        self.rows = rows.into_iter().map(|(_, cells)| cells).collect();
+        self.sort = Some((column, ascending));
+    }
+
+    /// The separators between columns and below the header, and the size of the gap between columns
+    fn separators(&self) -> (Option<(&str, &str, &str)>, Cell) {
+        match (self.border.connected_chars(), self.border.box_chars()) {
+            (Some([h, v, .., cross]), _) => (Some((h, v, cross)), 1),
+            (None, Some([h, v, ..])) => (Some((h, v, h)), 1),
+            _ => (None, self.border.gap()),
+        }
+    }
+
+    /// The column and width of every column, within some width
+    fn column_areas(&self, col: Cell, width: Cell) -> Vec<(Cell, Cell)> {
+        let (_, gap) = self.separators();
+        let sizes: Vec<_> = self.columns.iter().map(|column| column.width).collect();
+
+        let mut offset = col;
+        split_sizes(&sizes, width, gap).into_iter()
+            .map(|width| {
+                let area = (offset, width);
+                offset += width + gap;
+                area
+            })
+            .collect()
+    }
+
+    fn move_by(&mut self, amount: isize) {
+        self.select(self.selected.saturating_add_signed(amount));
+    }
+}
+
+impl Widget for Table {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        let (separators, _) = self.separators();
+        let header_height = if separators.is_some() { 2 } else { 1 };
+        if width == 0 || height <= header_height {
+            return
+        }
+
+        let body_row = row + header_height;
+        let body_height = height - header_height;
+        let overflows = self.rows.len() > body_height as usize;
+        let body_width = if overflows { width - 1 } else { width };
+
+        // keep the selected row visible
+        let visible = body_height as usize;
+        let mut scroll = self.scroll.get().min(self.selected);
+        if self.selected >= scroll + visible {
+            scroll = self.selected + 1 - visible;
+        }
+        let scroll = scroll.min(self.rows.len().saturating_sub(visible));
+        self.scroll.set(scroll);
+        self.size.set((body_width, body_height));
+
+        canvas.clear_rect(col, row, width, height);
+
+        let areas = self.column_areas(col, body_width);
+        for (i, (column, (column_col, column_width))) in self.columns.iter().zip(&areas).enumerate() {
+            let title = match self.sort {
+                Some((sorted, ascending)) if sorted == i =>
+                    format!("{} {}", column.title, if ascending { "▲" } else { "▼" }),
+                _ => column.title.clone(),
+            };
+            let line = Line::from(Span::styled(title, self.header_style.clone())).align(column.align);
+            canvas.addline(*column_col, row, *column_width, &line);
+        }
+
+        for (i, cells) in self.rows.iter().enumerate().skip(scroll).take(visible) {
+            let cell_row = body_row + (i - scroll) as Cell;
+            let style = if i == self.selected {
+                canvas.draw_hbar(col, cell_row, body_width, " ", Some(&self.highlight_style));
+                Some(self.highlight_style.clone())
+            } else {
+                self.style.clone()
+            };
+
+            for ((column, (column_col, column_width)), cell) in self.columns.iter().zip(&areas).zip(cells) {
+                let span = Span { content: cell.clone(), style: style.clone() };
+                canvas.addline(*column_col, cell_row, *column_width, &Line::from(span).align(column.align));
+            }
+        }
+
+        if let Some((h, v, cross)) = separators {
+            canvas.draw_hbar(col, row + 1, width, h, self.style.as_ref());
+            for (column_col, column_width) in areas.iter().take(areas.len().saturating_sub(1)) {
+                let separator_col = column_col + column_width;
+                canvas.addstr(separator_col, row, v, self.style.as_ref());
+                canvas.addstr(separator_col, row + 1, cross, self.style.as_ref());
+                canvas.draw_vbar(separator_col, body_row, body_height, v, self.style.as_ref());
+            }
+        }
+
+        list::draw_scrollbar(canvas, col + body_width, body_row, body_height, scroll, self.rows.len(),
+            self.style.as_ref());
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let (width, height) = self.size.get();
+        let header_height = if self.separators().0.is_some() { 2 } else { 1 };
+
+        let bytes = match event {
+            Event::Key(bytes) => bytes,
+            Event::Mouse { col, row: 0, pressed: true, .. } => {
+                let column = self.column_areas(0, width).iter()
+                    .position(|(column_col, column_width)| (*column_col..column_col + column_width).contains(&col));
+                let Some(column) = column else {
+                    return EventResult::Ignored
+                };
+                let ascending = self.sort != Some((column, true));
+                self.sort_by(column, ascending);
+                return EventResult::Consumed
+            }
+            Event::Mouse { row, pressed: true, .. } if row >= header_height => {
+                let index = self.scroll.get() + (row - header_height) as usize;
+                if index < self.rows.len() {
+                    self.selected = index;
+                    return EventResult::Consumed
+                }
+                return EventResult::Ignored
+            }
+            _ => return EventResult::Ignored,
+        };
+
+        let bytes = &bytes[..];
+        let page = height.max(1) as isize;
+
+        match bytes {
+            ARROW_UP => self.move_by(-1),
+            ARROW_DOWN => self.move_by(1),
+            PG_UP => self.move_by(-page),
+            PG_DOWN => self.move_by(page),
+            _ if HOME.contains(&bytes) => self.select(0),
+            _ if END.contains(&bytes) => self.select(self.rows.len()),
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (self.columns.len() as Cell, if self.separators().0.is_some() { 3 } else { 2 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[[&str; 2]]) -> Table {
+        let columns = vec![Column::new("name", PaneSize::Relative(1)), Column::new("size", PaneSize::Fixed(6))];
+        let rows = rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect()).collect();
+        Table::new(columns, rows)
+    }
+
+    fn names(table: &Table) -> Vec<&str> {
+        table.rows().iter().map(|row| row[0].as_str()).collect()
+    }
+
+    #[test]
+    fn split_widths() {
+        use PaneSize::*;
+
+        assert_eq!(split_sizes(&[Fixed(4), Relative(1), Relative(1)], 20, 1), [4, 7, 7]);
+        // what is lost to rounding goes to the first relative columns
+        assert_eq!(split_sizes(&[Relative(1), Relative(1), Relative(1)], 11, 0), [4, 4, 3]);
+        assert_eq!(split_sizes(&[Relative(1), Relative(2)], 9, 0), [3, 6]);
+        // fixed columns are cut when there is not enough room, in order
+        assert_eq!(split_sizes(&[Fixed(6), Fixed(6), Relative(1)], 10, 1), [6, 2, 0]);
+        assert_eq!(split_sizes(&[Relative(0), Relative(1)], 5, 0), [0, 5]);
+        assert_eq!(split_sizes(&[], 5, 1), []);
+    }
+
+    #[test]
+    fn cells_compare_as_numbers() {
+        assert_eq!(compare_cells("9", "10"), Ordering::Less);
+        assert_eq!(compare_cells(" 2.5", "-1"), Ordering::Greater);
+        assert_eq!(compare_cells("b", "a"), Ordering::Greater);
+        assert_eq!(compare_cells("10", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn sort_keeping_the_selection() {
+        let mut table = table(&[["c", "10"], ["a", "9"], ["b", "100"]]);
+        table.select(1);
+
+        table.sort_by(0, true);
+        assert_eq!(names(&table), ["a", "b", "c"]);
+        assert_eq!(table.selected(), Some(0));
+
+        table.sort_by(1, false);
+        assert_eq!(names(&table), ["b", "c", "a"]);
+        assert_eq!(table.selected(), Some(2));
+        assert_eq!(table.sorted(), Some((1, false)));
+
+        // out of range columns are ignored
+        table.sort_by(2, true);
+        assert_eq!(table.sorted(), Some((1, false)));
+
+        table.set_rows(vec![vec!["d".into(), "1".into()], vec!["e".into(), "2".into()]]);
+        assert_eq!(names(&table), ["e", "d"]);
+        assert_eq!(table.selected(), Some(0));
+    }
+
+    #[test]
+    fn click_titles_to_sort() {
+        let mut table = table(&[["b", "1"], ["a", "2"]]);
+        let mut canvas = InstructionBuffer::new(20, 5, None);
+        table.render((0, 0, 20, 5), &mut canvas);
+
+        let click = |col| Event::Mouse { button: 0, col, row: 0, pressed: true };
+        table.handle_event(click(2));
+        assert_eq!(table.sorted(), Some((0, true)));
+        assert_eq!(names(&table), ["a", "b"]);
+
+        table.handle_event(click(2));
+        assert_eq!(table.sorted(), Some((0, false)));
+
+        table.handle_event(click(16));
+        assert_eq!(table.sorted(), Some((1, true)));
+        assert_eq!(names(&table), ["b", "a"]);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::*;
+use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
+use std::io;
+use std::sync::{Mutex, Once};
+use std::thread::{self, ThreadId};
+
+/// The options of the active `Terminal` and the thread which entered it, if any,
+/// so that it is restored only once
+static ACTIVE: Mutex<Option<(TerminalOptions, ThreadId)>> = Mutex::new(None);
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Undo what entering the terminal did, in reverse order, if it has not been undone yet.
+/// Given a thread, only restores if the terminal was entered on that thread.
+fn restore(thread: Option<ThreadId>) -> Result<(), io::Error> {
+    let mut active = ACTIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some((options, _)) = active.take_if(|(_, owner)| thread.is_none_or(|thread| thread == *owner)) else {
+        return Ok(())
+    };
+    drop(active);
+
+    if options.paste { print!("{PASTE_DISABLE}"); }
+    if options.mouse { print!("{MOUSE_DISABLE}"); }
+    print!("{RESET}{CUR_SHOW}{ALT_SCREEN_LEAVE}");
+    utils::flush();
+    disable_raw_mode()
+}
+
+/// What to enable when entering a `Terminal`, besides raw mode, the alternate screen and
+/// hiding the cursor
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct TerminalOptions {
+    /// Report mouse events, see `MOUSE_ENABLE`
+    pub mouse: bool,
+    /// Bracketed paste, see `PASTE_ENABLE`
+    pub paste: bool,
+}
+
+impl TerminalOptions {
+    pub fn mouse(mut self) -> Self {
+        self.mouse = true;
+        self
+    }
+
+    pub fn paste(mut self) -> Self {
+        self.paste = true;
+        self
+    }
+
+    /// Set up the terminal, which is restored when the returned guard is dropped or the thread
+    /// entering it panics. Fails if another `Terminal` is active.
+    pub fn enter(self) -> Result<Terminal, io::Error> {
+        // restore before the panic message is printed, so that it is shown on the main screen
+        PANIC_HOOK.call_once(|| {
+            let hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                let _ = restore(Some(thread::current().id()));
+                hook(info);
+            }));
+        });
+
+        let mut active = ACTIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if active.is_some() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "a Terminal is already active"))
+        }
+
+        enable_raw_mode()?;
+        *active = Some((self, thread::current().id()));
+        drop(active);
+
+        print!("{ALT_SCREEN_ENTER}{ERASE_SCREEN}{CUR_HOME}{RESET}{CUR_HIDE}");
+        if self.mouse { print!("{MOUSE_ENABLE}"); }
+        if self.paste { print!("{PASTE_ENABLE}"); }
+        utils::flush();
+
+        Ok(Terminal { options: self })
+    }
+}
+
+/// Keeps the terminal in raw mode on the alternate screen with the cursor hidden, until dropped.
+/// Replaces pairing `utils::init_term` and `utils::restore_term` by hand.
+pub struct Terminal {
+    options: TerminalOptions,
+}
+
+impl Terminal {
+    /// Set up the terminal without mouse events or bracketed paste
+    pub fn new() -> Result<Self, io::Error> {
+        TerminalOptions::default().enter()
+    }
+
+    pub fn options() -> TerminalOptions {
+        TerminalOptions::default()
+    }
+
+    pub fn mouse(&self) -> bool {
+        self.options.mouse
+    }
+
+    pub fn paste(&self) -> bool {
+        self.options.paste
+    }
+
+    /// Restore the terminal now, returning any error instead of ignoring it like dropping does
+    pub fn restore(self) -> Result<(), io::Error> {
+        restore(None)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = restore(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_one_terminal_is_active() {
+        let owner = thread::current().id();
+        *ACTIVE.lock().unwrap() = Some((TerminalOptions::default(), owner));
+
+        let error = TerminalOptions::default().mouse().enter().err().map(|error| error.kind());
+        assert_eq!(error, Some(io::ErrorKind::AlreadyExists));
+
+        // a panic in another thread leaves the terminal alone
+        let other = thread::spawn(|| thread::current().id()).join().unwrap();
+        assert!(restore(Some(other)).is_ok());
+        assert_eq!(*ACTIVE.lock().unwrap(), Some((TerminalOptions::default(), owner)));
+
+        *ACTIVE.lock().unwrap() = None;
+    }
+}
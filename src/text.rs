@@ -0,0 +1,423 @@
+use crate::*;
+
+/// A string drawn with a single style
+#[derive(Clone, PartialEq)]
+pub struct Span {
+    pub content: Str,
+    /// The default style of the canvas is used if this is `None`
+    pub style: Option<Style>,
+}
+
+impl Span {
+    pub fn new(content: impl Into<Str>) -> Self {
+        Self {
+            content: content.into(),
+            style: None,
+        }
+    }
+
+    pub fn styled(content: impl Into<Str>, style: Style) -> Self {
+        Self {
+            content: content.into(),
+            style: Some(style),
+        }
+    }
+
+    pub fn width(&self) -> Cell {
+        utils::display_width(&self.content) as Cell
+    }
+}
+
+impl From<&str> for Span {
+    fn from(content: &str) -> Self {
+        Self::new(content)
+    }
+}
+
+impl From<Str> for Span {
+    fn from(content: Str) -> Self {
+        Self::new(content)
+    }
+}
+
+/// Spans drawn after each other on a single row
+#[derive(Clone, PartialEq, Default)]
+pub struct Line {
+    pub spans: Vec<Span>,
+    pub align: Align,
+}
+
+impl Line {
+    pub fn new(spans: Vec<Span>) -> Self {
+        Self {
+            spans,
+            align: Align::Left,
+        }
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn push(mut self, span: impl Into<Span>) -> Self {
+        self.spans.push(span.into());
+        self
+    }
+
+    pub fn width(&self) -> Cell {
+        self.spans.iter().map(Span::width).sum()
+    }
+}
+
+impl From<Span> for Line {
+    fn from(span: Span) -> Self {
+        Self::new(vec![span])
+    }
+}
+
+impl From<&str> for Line {
+    fn from(content: &str) -> Self {
+        Self::new(vec![content.into()])
+    }
+}
+
+impl From<Vec<Span>> for Line {
+    fn from(spans: Vec<Span>) -> Self {
+        Self::new(spans)
+    }
+}
+
+/// Lines drawn below each other
+#[derive(Clone, PartialEq, Default)]
+pub struct Text {
+    pub lines: Vec<Line>,
+}
+
+impl Text {
+    pub fn new(lines: Vec<Line>) -> Self {
+        Self { lines }
+    }
+
+    /// Parse text with styles given in brackets, like `[bold red]Error:[/] file not found`.
+    ///
+    /// A tag holds any of `bold`, `dim`, `italic`, `underline`, `blink` and `strike`, a
+    /// foreground color, and a background color following `on`, with colors written as
+    /// understood by `Color::from_str`. Tags can be nested, and `[/]` ends the last one.
+    /// `[[` is a literal bracket, and lines are separated by `\n`.
+    pub fn from_markup(string: &str) -> Result<Self, ParseError> {
+        let mut lines = vec![Line::default()];
+        let mut styles: Vec<Style> = Vec::new();
+        let mut content = Str::new();
+        let mut chars = string.char_indices().peekable();
+
+        // end the current span, if there is one
+        let flush = |lines: &mut Vec<Line>, content: &mut Str, styles: &[Style]| {
+            if !content.is_empty() {
+                let span = Span { content: std::mem::take(content), style: styles.last().cloned() };
+                lines.last_mut().unwrap().spans.push(span);
+            }
+        };
+
+        while let Some((position, ch)) = chars.next() {
+            match ch {
+                '[' if chars.next_if(|(_, ch)| *ch == '[').is_some() => content.push('['),
+                '[' => {
+                    let start = position + 1;
+                    let Some(len) = string[start..].find(']') else {
+                        return Err(ParseError { position, message: "unclosed tag" })
+                    };
+                    let tag = &string[start..start + len];
+                    while chars.next_if(|(i, _)| *i <= start + len).is_some() {}
+
+                    flush(&mut lines, &mut content, &styles);
+
+                    if tag.starts_with('/') {
+                        if styles.pop().is_none() {
+                            return Err(ParseError { position, message: "no tag to end" })
+                        }
+                    } else {
+                        let parent = styles.last().cloned().unwrap_or_else(Style::new);
+                        styles.push(parse_tag(tag, start, parent)?);
+                    }
+                }
+                '\n' => {
+                    flush(&mut lines, &mut content, &styles);
+                    lines.push(Line::default());
+                }
+                _ => content.push(ch),
+            }
+        }
+
+        flush(&mut lines, &mut content, &styles);
+        Ok(Self { lines })
+    }
+
+    /// Convert text colored with SGR escapes, like the output of `git diff --color`.
+    /// Other escape sequences and control characters are removed, and tabs are expanded.
+    pub fn from_ansi(string: &str) -> Self {
+        let mut lines = vec![Line::default()];
+        let mut style = None;
+
+        for chunk in chunks(string) {
+            match chunk {
+                Chunk::Text(text) => {
+                    for (i, part) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            lines.push(Line::default());
+                        }
+
+                        let line = lines.last_mut().unwrap();
+                        let mut content = Str::new();
+                        let mut col = line.width() as usize;
+
+                        for grapheme in part.graphemes(true) {
+                            match grapheme {
+                                "\t" => {
+                                    let spaces = TAB_WIDTH - col % TAB_WIDTH;
+                                    content.push_str(&" ".repeat(spaces));
+                                    col += spaces;
+                                }
+                                _ if grapheme.chars().all(char::is_control) => {}
+                                _ => {
+                                    content.push_str(grapheme);
+                                    col += utils::grapheme_width(grapheme);
+                                }
+                            }
+                        }
+
+                        if content.is_empty() {
+                            continue
+                        }
+
+                        // continue the last span if the style has not changed
+                        match line.spans.last_mut() {
+                            Some(span) if span.style == style => span.content.push_str(&content),
+                            _ => line.spans.push(Span { content, style: style.clone() }),
+                        }
+                    }
+                }
+                Chunk::Csi(params, 'm') => apply_sgr(params, &mut style),
+                Chunk::Csi(..) | Chunk::Other(..) => {}
+            }
+        }
+
+        Self { lines }
+    }
+
+    pub fn width(&self) -> Cell {
+        self.lines.iter().map(Line::width).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> Cell {
+        self.lines.len() as Cell
+    }
+}
+
+impl From<Line> for Text {
+    fn from(line: Line) -> Self {
+        Self::new(vec![line])
+    }
+}
+
+impl From<&str> for Text {
+    fn from(content: &str) -> Self {
+        Self::new(content.split('\n').map(Line::from).collect())
+    }
+}
+
+const TAB_WIDTH: usize = 8;
+
+/// Colors in the order of their SGR codes
+const COLORS: [Color; 16] = [
+    Color::Black, Color::Red, Color::Green, Color::Yellow,
+    Color::Blue, Color::Magenta, Color::Cyan, Color::White,
+    Color::BrightBlack, Color::BrightRed, Color::BrightGreen, Color::BrightYellow,
+    Color::BrightBlue, Color::BrightMagenta, Color::BrightCyan, Color::BrightWhite,
+];
+
+/// A part of a string containing escape sequences
+pub(crate) enum Chunk<'a> {
+    Text(&'a str),
+    /// The parameters and final character of a control sequence, like `\x1b[1;31m`
+    Csi(&'a str, char),
+    /// Any other escape sequence, such as an operating system command
+    Other(&'a str),
+}
+
+/// Split a string into text and escape sequences
+pub(crate) fn chunks(string: &str) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut rest = string;
+
+    while let Some(start) = rest.find('\x1b') {
+        if start > 0 {
+            chunks.push(Chunk::Text(&rest[..start]));
+        }
+
+        let sequence = &rest[start + 1..];
+        let len = match sequence.chars().next() {
+            Some('[') => {
+                // parameter and intermediate bytes, followed by a final byte
+                let end = sequence[1..].find(|ch: char| !('\x20'..='\x3f').contains(&ch));
+                match end.map(|end| (end + 1, sequence[end + 1..].chars().next().unwrap())) {
+                    Some((end, ch)) if ('\x40'..='\x7e').contains(&ch) => {
+                        chunks.push(Chunk::Csi(&sequence[1..end], ch));
+                        rest = &sequence[end + 1..];
+                        continue
+                    }
+                    Some((end, _)) => end,
+                    None => sequence.len(),
+                }
+            }
+            Some(']' | 'P' | '_' | '^') => {
+                // terminated by a bell or a string terminator
+                let end = sequence.find(['\x07', '\x1b']).unwrap_or(sequence.len());
+                let terminator = if sequence[end..].starts_with("\x1b\\") { 2 } else { 1 };
+                (end + terminator).min(sequence.len())
+            }
+            Some(ch) => ch.len_utf8(),
+            None => 0,
+        };
+
+        chunks.push(Chunk::Other(&rest[start..start + 1 + len]));
+        rest = &sequence[len..];
+    }
+
+    if !rest.is_empty() {
+        chunks.push(Chunk::Text(rest));
+    }
+
+    chunks
+}
+
+/// Apply the parameters of an SGR sequence to a style, where `None` is the default style
+fn apply_sgr(params: &str, style: &mut Option<Style>) {
+    let mut params = params.split(';');
+
+    while let Some(param) = params.next() {
+        // subparameters after a colon only matter for extended colors
+        let code = parse_code(param.split(':').next().unwrap_or(""));
+        if code == 0 {
+            *style = None;
+            continue
+        }
+
+        let style = style.get_or_insert_with(Style::new);
+        match code {
+            1 => style.bold = true,
+            2 => style.dim = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            5 | 6 => style.blink = true,
+            9 => style.strike = true,
+            22 => (style.bold, style.dim) = (false, false),
+            23 => style.italic = false,
+            24 => style.underline = false,
+            25 => style.blink = false,
+            29 => style.strike = false,
+            30..=37 => style.fg = Some(COLORS[code as usize - 30].clone()),
+            38 => style.fg = extended_color(param, &mut params).or(style.fg.take()),
+            39 => style.fg = Some(Color::Default),
+            40..=47 => style.bg = Some(COLORS[code as usize - 40].clone()),
+            48 => style.bg = extended_color(param, &mut params).or(style.bg.take()),
+            49 => style.bg = Some(Color::Default),
+            90..=97 => style.fg = Some(COLORS[code as usize - 82].clone()),
+            100..=107 => style.bg = Some(COLORS[code as usize - 92].clone()),
+            _ => {}
+        }
+    }
+}
+
+fn parse_code(code: &str) -> u16 {
+    code.parse().unwrap_or(0)
+}
+
+/// Read the rest of a 256 or true color code, either from the subparameters
+/// of `38:2::r:g:b` or from the parameters following `38;2;r;g;b`
+fn extended_color<'a>(param: &str, params: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    if param.contains(':') {
+        let codes: Vec<u16> = param.split(':').skip(1).map(parse_code).collect();
+        return match codes[..] {
+            [5, index, ..] => Some(Color::Ansi(index as u8)),
+            // the color space id before the components may be left out
+            [2, _, r, g, b, ..] | [2, r, g, b] => Some(Color::True(r as u8, g as u8, b as u8)),
+            _ => None,
+        }
+    }
+
+    let mut codes = params.map(parse_code);
+    match codes.next()? {
+        5 => Some(Color::Ansi(codes.next()? as u8)),
+        2 => Some(Color::True(codes.next()? as u8, codes.next()? as u8, codes.next()? as u8)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(text: &Text) -> Vec<Vec<&str>> {
+        text.lines.iter().map(|line| line.spans.iter().map(|span| span.content.as_str()).collect()).collect()
+    }
+
+    #[test]
+    fn parse_markup() {
+        let text = Text::from_markup("[bold red]Error:[/] file [[1] [on blue]not [italic]found[/][/]\nok").unwrap();
+        assert_eq!(contents(&text), [vec!["Error:", " file [1] ", "not ", "found"], vec!["ok"]]);
+
+        let spans = &text.lines[0].spans;
+        assert!(spans[0].style == Some(Style::new().bold().fg(Color::Red)));
+        assert!(spans[1].style.is_none());
+        assert!(spans[2].style == Some(Style::new().bg(Color::Blue)));
+        // nested tags add to the style of their parent
+        assert!(spans[3].style == Some(Style::new().bg(Color::Blue).italic()));
+    }
+
+    #[test]
+    fn markup_errors() {
+        let error = |markup| Text::from_markup(markup).err();
+        assert_eq!(error("a [bold"), Some(ParseError { position: 2, message: "unclosed tag" }));
+        assert_eq!(error("a[/]"), Some(ParseError { position: 1, message: "no tag to end" }));
+        assert_eq!(error("[bold nope]"), Some(ParseError { position: 6, message: "unknown style" }));
+        assert_eq!(error("[red on]").map(|error| error.message), Some("expected a color after `on`"));
+    }
+
+    #[test]
+    fn parse_ansi() {
+        let text = Text::from_ansi("\x1b[1;31mred\x1b[22m plain\x1b[0m\x1b]0;title\x07 end\nx\x1b[38;5;208my\x1b[48;2;1;2;3mz");
+        assert_eq!(contents(&text), [vec!["red", " plain", " end"], vec!["x", "y", "z"]]);
+
+        let spans = &text.lines[0].spans;
+        assert!(spans[0].style == Some(Style::new().bold().fg(Color::Red)));
+        assert!(spans[1].style == Some(Style::new().fg(Color::Red)));
+        assert!(spans[2].style.is_none());
+
+        let spans = &text.lines[1].spans;
+        assert!(spans[1].style == Some(Style::new().fg(Color::Ansi(208))));
+        assert!(spans[2].style == Some(Style::new().fg(Color::Ansi(208)).bg(Color::True(1, 2, 3))));
+    }
+
+    #[test]
+    fn ansi_colon_colors() {
+        let text = Text::from_ansi("\x1b[38:2::1:2:3;1ma\x1b[48:2:4:5:6mb\x1b[38:5:208;4mc");
+        let spans = &text.lines[0].spans;
+        assert!(spans[0].style == Some(Style::new().fg(Color::True(1, 2, 3)).bold()));
+        assert!(spans[1].style == Some(Style::new().fg(Color::True(1, 2, 3)).bold().bg(Color::True(4, 5, 6))));
+        assert!(spans[2].style == Some(Style::new().fg(Color::Ansi(208)).bold().bg(Color::True(4, 5, 6)).underline()));
+    }
+
+    #[test]
+    fn ansi_tabs_stop_by_columns() {
+        let text = Text::from_ansi("a\tb\n漢\tc\r\x07");
+        assert_eq!(contents(&text), [vec!["a       b"], vec!["漢      c"]]);
+    }
+
+    #[test]
+    fn span_widths() {
+        assert_eq!(Span::new("漢字ab").width(), 6);
+        assert_eq!(Line::from("ab").push("漢").width(), 4);
+        assert_eq!(Text::from("a\n漢字").width(), 4);
+    }
+}
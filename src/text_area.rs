@@ -0,0 +1,573 @@
+use crate::*;
+use crate::input_sequences::*;
+use crate::text_input::{word_start, word_end, typed, fitting};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A line and the index of a grapheme in it
+type Position = (usize, usize);
+
+/// The byte index of a grapheme index
+fn byte_index(line: &str, i: usize) -> usize {
+    line.grapheme_indices(true).nth(i).map_or(line.len(), |(byte, _)| byte)
+}
+
+/// The amount of columns every grapheme of a line takes up
+fn widths(line: &str) -> Vec<usize> {
+    line.graphemes(true).map(utils::grapheme_width).collect()
+}
+
+/// A multi line text editor.
+///
+/// Text is selected with `SHIFT_ARROW_*`, changes are undone with `CTRL_Z` and redone with
+/// `CTRL_Y`, and pasted text is inserted as is. `TAB` is left to the application, so that it
+/// can be used for moving focus.
+pub struct TextArea {
+    lines: Vec<Str>,
+    cursor: Position,
+    /// The other end of the selection, if any
+    anchor: Option<Position>,
+    wrap: bool,
+    /// The first row and column shown, updated when rendering
+    scroll: std::cell::Cell<(usize, usize)>,
+    /// The width and height when last rendered, used for moving by pages and clicking
+    size: std::cell::Cell<(Cell, Cell)>,
+    undo: Vec<(Vec<Str>, Position)>,
+    redo: Vec<(Vec<Str>, Position)>,
+    /// Whether the last change was typing, so that words are not undone by the grapheme
+    typing: bool,
+    style: Option<Style>,
+    selection_style: Style,
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextArea {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![Str::new()],
+            cursor: (0, 0),
+            anchor: None,
+            wrap: false,
+            scroll: std::cell::Cell::new((0, 0)),
+            size: std::cell::Cell::new((1, 1)),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            typing: false,
+            style: None,
+            selection_style: Style::new().fg(Color::Black).bg(Color::White),
+        }
+    }
+
+    /// Wrap long lines instead of scrolling horizontally
+    pub fn wrap(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn selection_style(mut self, style: Style) -> Self {
+        self.selection_style = style;
+        self
+    }
+
+    pub fn content(&self) -> Str {
+        self.lines.join("\n")
+    }
+
+    /// Replace the content, moving the cursor to the start and forgetting the undo history
+    pub fn set_content(&mut self, content: &str) {
+        self.lines = content.replace("\r\n", "\n").split('\n').map(str::to_string).collect();
+        self.cursor = (0, 0);
+        self.anchor = None;
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// The line and grapheme index of the cursor
+    pub fn position(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// The selected text, if any
+    pub fn selection(&self) -> Option<Str> {
+        let (start, end) = self.selection_range()?;
+        let mut text = Str::new();
+
+        for line in start.0..=end.0 {
+            let content = &self.lines[line];
+            let from = if line == start.0 { byte_index(content, start.1) } else { 0 };
+            let to = if line == end.0 { byte_index(content, end.1) } else { content.len() };
+
+            if line > start.0 {
+                text.push('\n');
+            }
+            text.push_str(&content[from..to]);
+        }
+
+        Some(text)
+    }
+
+    fn selection_range(&self) -> Option<(Position, Position)> {
+        let anchor = self.anchor.filter(|anchor| *anchor != self.cursor)?;
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    fn line_len(&self, line: usize) -> usize {
+        self.lines[line].graphemes(true).count()
+    }
+
+    /// Insert text at the cursor, replacing the selection
+    pub fn insert(&mut self, text: &str) {
+        self.save(false);
+        self.insert_text(text);
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        self.delete_selection();
+
+        let (line, col) = self.cursor;
+        let byte = byte_index(&self.lines[line], col);
+        let after = self.lines[line].split_off(byte);
+        let text = text.replace("\r\n", "\n");
+        let mut parts = text.split('\n');
+
+        self.lines[line].push_str(parts.next().unwrap_or_default());
+        let mut cursor = (line, self.line_len(line));
+
+        for (i, part) in parts.enumerate() {
+            self.lines.insert(line + i + 1, part.to_string());
+            cursor = (line + i + 1, part.graphemes(true).count());
+        }
+
+        self.lines[cursor.0].push_str(&after);
+        self.cursor = cursor;
+    }
+
+    /// Remove the text between two positions
+    fn delete(&mut self, start: Position, end: Position) {
+        let end_byte = byte_index(&self.lines[end.0], end.1);
+        let after = self.lines[end.0][end_byte..].to_string();
+        let start_byte = byte_index(&self.lines[start.0], start.1);
+
+        self.lines[start.0].truncate(start_byte);
+        self.lines[start.0].push_str(&after);
+        self.lines.drain(start.0 + 1..=end.0);
+        self.cursor = start;
+        self.anchor = None;
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selection_range() {
+            Some((start, end)) => {
+                self.delete(start, end);
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    /// Remember the content before a change, so that it can be undone
+    fn save(&mut self, typing: bool) {
+        if !(typing && self.typing) {
+            self.undo.push((self.lines.clone(), self.cursor));
+            self.redo.clear();
+        }
+        self.typing = typing;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some((lines, cursor)) = self.undo.pop() else {
+            return false
+        };
+
+        self.redo.push((std::mem::replace(&mut self.lines, lines), self.cursor));
+        self.cursor = cursor;
+        self.anchor = None;
+        self.typing = false;
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some((lines, cursor)) = self.redo.pop() else {
+            return false
+        };
+
+        self.undo.push((std::mem::replace(&mut self.lines, lines), self.cursor));
+        self.cursor = cursor;
+        self.anchor = None;
+        self.typing = false;
+        true
+    }
+
+    /// Move the cursor, selecting the text passed over if `select` is true
+    fn move_to(&mut self, position: Position, select: bool) {
+        if !select {
+            self.anchor = None;
+        } else if self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
+        }
+
+        self.cursor = position;
+        self.typing = false;
+    }
+
+    fn left(&self) -> Position {
+        match self.cursor {
+            (0, 0) => (0, 0),
+            (line, 0) => (line - 1, self.line_len(line - 1)),
+            (line, col) => (line, col - 1),
+        }
+    }
+
+    fn right(&self) -> Position {
+        let (line, col) = self.cursor;
+        match col < self.line_len(line) {
+            true => (line, col + 1),
+            false if line + 1 < self.lines.len() => (line + 1, 0),
+            false => (line, col),
+        }
+    }
+
+    /// The position some amount of lines up or down, in the same column if possible
+    fn vertical(&self, lines: isize) -> Position {
+        let line = self.cursor.0.saturating_add_signed(lines).min(self.lines.len() - 1);
+        (line, self.cursor.1.min(self.line_len(line)))
+    }
+
+    /// The line and the start and end of the graphemes shown on every row
+    fn rows(&self, width: Cell) -> Vec<(usize, usize, usize)> {
+        let width = (width as usize).max(1);
+        let mut rows = Vec::new();
+
+        for (line, content) in self.lines.iter().enumerate() {
+            let widths = widths(content);
+            if !self.wrap {
+                rows.push((line, 0, widths.len()));
+                continue
+            }
+
+            let mut start = 0;
+            loop {
+                // a grapheme wider than a row gets a row of its own
+                let end = (start + fitting(&widths[start..], width).max(1)).min(widths.len());
+                rows.push((line, start, end));
+
+                if end == widths.len() {
+                    // a full last row gets another after it when the cursor is at the end of the line
+                    if self.cursor == (line, end) && widths[start..].iter().sum::<usize>() >= width {
+                        rows.push((line, end, end));
+                    }
+                    break
+                }
+                start = end;
+            }
+        }
+
+        rows
+    }
+
+    /// The row of the cursor and its column relative to the start of the row
+    fn cursor_row(&self, rows: &[(usize, usize, usize)]) -> (usize, usize) {
+        let row = rows.iter()
+            .rposition(|(line, start, _)| *line == self.cursor.0 && *start <= self.cursor.1)
+            .unwrap_or(0);
+        let widths = widths(&self.lines[self.cursor.0]);
+        (row, widths[rows[row].1..self.cursor.1].iter().sum())
+    }
+
+    /// The first row and column to show, so that the cursor is visible
+    fn scroll_to_cursor(&self, width: Cell, height: Cell) -> (usize, usize) {
+        let rows = self.rows(width);
+        let (row, col) = self.cursor_row(&rows);
+        let (mut top, mut left) = self.scroll.get();
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+
+        top = top.min(row);
+        if row >= top + height {
+            top = row + 1 - height;
+        }
+
+        // the whole grapheme at the cursor is shown, or a column after the end of the line
+        let cursor_width = widths(&self.lines[self.cursor.0]).get(self.cursor.1).map_or(1, |w| (*w).max(1));
+        left = if self.wrap { 0 } else { left.min(col) };
+        if col + cursor_width > left + width {
+            left = (col + cursor_width - width).min(col);
+        }
+
+        (top, left)
+    }
+}
+
+impl Widget for TextArea {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        let (top, left) = self.scroll_to_cursor(width, height);
+        self.scroll.set((top, left));
+        self.size.set((width, height));
+
+        let selection = self.selection_range();
+        canvas.fill_rect(col, row, width, height, " ", self.style.as_ref());
+
+        for (i, (line, start, end)) in self.rows(width).into_iter().skip(top).take(height.into()).enumerate() {
+            let graphemes: Vec<_> = self.lines[line].graphemes(true).collect();
+            let widths = widths(&self.lines[line]);
+
+            // the graphemes entirely within the columns shown, and the column of each
+            let mut x = 0;
+            let shown: Vec<_> = (start..end).filter_map(|j| {
+                let column = x;
+                x += widths[j];
+                (column >= left && x <= left + width as usize).then(|| (j, column - left))
+            }).collect();
+
+            let Some(&(start, _)) = shown.first() else {
+                continue
+            };
+            let end = start + shown.len();
+
+            // the part of the row which is selected
+            let (selected_start, selected_end) = match selection {
+                Some(((first, from), (last, to))) if first <= line && line <= last => (
+                    if line == first { from.clamp(start, end) } else { start },
+                    if line == last { to.clamp(start, end) } else { end },
+                ),
+                _ => (end, end),
+            };
+
+            let parts = [
+                (start, selected_start, self.style.as_ref()),
+                (selected_start, selected_end, Some(&self.selection_style)),
+                (selected_end, end, self.style.as_ref()),
+            ];
+
+            for (from, to, style) in parts.into_iter().filter(|(from, to, _)| from < to) {
+                let text = graphemes[from..to].concat();
+                canvas.addstr(col + shown[from - start].1 as Cell, row + i as Cell, &text, style);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let bytes = match event {
+            Event::Key(bytes) => bytes,
+            Event::Paste(text) => {
+                self.insert(&text);
+                return EventResult::Consumed
+            }
+            Event::Mouse { col, row, pressed: true, .. } => {
+                let (top, left) = self.scroll.get();
+                let rows = self.rows(self.size.get().0);
+                if let Some((line, start, end)) = rows.get(top + row as usize).copied() {
+                    // the cursor goes before the grapheme covering the column
+                    let widths = widths(&self.lines[line]);
+                    self.move_to((line, start + fitting(&widths[start..end], left + col as usize)), false);
+                }
+                return EventResult::Consumed
+            }
+            _ => return EventResult::Ignored,
+        };
+
+        let bytes = &bytes[..];
+        let (line, col) = self.cursor;
+        let graphemes: Vec<_> = self.lines[line].graphemes(true).collect();
+        let page = self.size.get().1.max(1) as isize;
+
+        match bytes {
+            ARROW_LEFT => self.move_to(self.left(), false),
+            ARROW_RIGHT => self.move_to(self.right(), false),
+            ARROW_UP => self.move_to(self.vertical(-1), false),
+            ARROW_DOWN => self.move_to(self.vertical(1), false),
+            SHIFT_ARROW_LEFT => self.move_to(self.left(), true),
+            SHIFT_ARROW_RIGHT => self.move_to(self.right(), true),
+            SHIFT_ARROW_UP => self.move_to(self.vertical(-1), true),
+            SHIFT_ARROW_DOWN => self.move_to(self.vertical(1), true),
+            CTRL_ARROW_LEFT => self.move_to((line, word_start(&graphemes, col)), false),
+            CTRL_ARROW_RIGHT => self.move_to((line, word_end(&graphemes, col)), false),
+            CTRL_SHIFT_ARROW_LEFT => self.move_to((line, word_start(&graphemes, col)), true),
+            CTRL_SHIFT_ARROW_RIGHT => self.move_to((line, word_end(&graphemes, col)), true),
+            PG_UP => self.move_to(self.vertical(-page), false),
+            PG_DOWN => self.move_to(self.vertical(page), false),
+            CTRL_A => self.move_to((line, 0), false),
+            CTRL_E => self.move_to((line, graphemes.len()), false),
+            _ if HOME.contains(&bytes) => self.move_to((line, 0), false),
+            _ if END.contains(&bytes) => self.move_to((line, graphemes.len()), false),
+
+            CTRL_Z => { self.undo(); }
+            CTRL_Y => { self.redo(); }
+
+            RETURN => self.insert("\n"),
+            BACKSPACE | DELETE if self.selection_range().is_some() => {
+                self.save(false);
+                self.delete_selection();
+            }
+            BACKSPACE if self.cursor != (0, 0) => {
+                self.save(false);
+                self.delete(self.left(), self.cursor);
+            }
+            DELETE if self.right() != self.cursor => {
+                self.save(false);
+                self.delete(self.cursor, self.right());
+            }
+            BACKSPACE | DELETE => {}
+            CTRL_BACKSPACE | CTRL_W => {
+                let start = if col == 0 { self.left() } else { (line, word_start(&graphemes, col)) };
+                self.save(false);
+                self.delete(start, self.cursor);
+            }
+
+            _ => match typed(bytes) {
+                Some(text) => {
+                    self.save(true);
+                    self.insert_text(text);
+                }
+                None => return EventResult::Ignored,
+            },
+        }
+
+        EventResult::Consumed
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (1, 1)
+    }
+
+    fn cursor(&self, (col, row, width, height): Area) -> Option<(Cell, Cell)> {
+        let (top, left) = self.scroll_to_cursor(width, height);
+        let (cursor_row, cursor_col) = self.cursor_row(&self.rows(width));
+        Some((col + (cursor_col - left) as Cell, row + (cursor_row - top) as Cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(area: &mut TextArea, bytes: &[u8]) {
+        area.handle_event(Event::Key(bytes.to_vec()));
+    }
+
+    fn typing(area: &mut TextArea, text: &str) {
+        for grapheme in text.graphemes(true) {
+            key(area, grapheme.as_bytes());
+        }
+    }
+
+    #[test]
+    fn type_lines() {
+        let mut area = TextArea::new();
+        typing(&mut area, "ab");
+        key(&mut area, RETURN);
+        typing(&mut area, "cd");
+        key(&mut area, ARROW_UP);
+        key(&mut area, ARROW_LEFT);
+        typing(&mut area, "\u{301}");
+
+        assert_eq!(area.content(), "a\u{301}b\ncd");
+        assert_eq!(area.position(), (0, 1));
+
+        key(&mut area, CTRL_E);
+        key(&mut area, DELETE);
+        assert_eq!(area.content(), "a\u{301}bcd");
+        key(&mut area, BACKSPACE);
+        assert_eq!(area.content(), "a\u{301}cd");
+        assert_eq!(area.position(), (0, 1));
+    }
+
+    #[test]
+    fn typed_words_are_undone_together() {
+        let mut area = TextArea::new();
+        typing(&mut area, "one");
+        key(&mut area, RETURN);
+        typing(&mut area, "two");
+
+        key(&mut area, CTRL_Z);
+        assert_eq!(area.content(), "one\n");
+        key(&mut area, CTRL_Z);
+        assert_eq!(area.content(), "one");
+        key(&mut area, CTRL_Z);
+        assert_eq!(area.content(), "");
+        assert!(!area.undo());
+
+        key(&mut area, CTRL_Y);
+        key(&mut area, CTRL_Y);
+        assert_eq!(area.content(), "one\n");
+        assert_eq!(area.position(), (1, 0));
+    }
+
+    #[test]
+    fn select_across_lines() {
+        let mut area = TextArea::new();
+        area.set_content("first\nsecond\nthird");
+        key(&mut area, ARROW_RIGHT);
+        key(&mut area, SHIFT_ARROW_DOWN);
+        key(&mut area, SHIFT_ARROW_DOWN);
+        key(&mut area, SHIFT_ARROW_RIGHT);
+        assert_eq!(area.selection().as_deref(), Some("irst\nsecond\nth"));
+
+        area.insert("X\nY");
+        assert_eq!(area.content(), "fX\nYird");
+        assert_eq!(area.position(), (1, 1));
+        assert_eq!(area.selection(), None);
+
+        key(&mut area, CTRL_Z);
+        assert_eq!(area.content(), "first\nsecond\nthird");
+    }
+
+    #[test]
+    fn wrapped_rows() {
+        let mut area = TextArea::new().wrap();
+        area.set_content("abcdef\n\nabc");
+        area.move_to((0, 0), false);
+        assert_eq!(area.rows(3), [(0, 0, 3), (0, 3, 6), (1, 0, 0), (2, 0, 3)]);
+
+        // a full row only gets another after it for the cursor at the end of its line
+        area.move_to((0, 6), false);
+        assert_eq!(area.rows(3), [(0, 0, 3), (0, 3, 6), (0, 6, 6), (1, 0, 0), (2, 0, 3)]);
+
+        area.move_to((0, 4), false);
+        assert_eq!(area.cursor((0, 0, 3, 2)), Some((1, 1)));
+        area.move_to((2, 3), false);
+        assert_eq!(area.cursor((0, 0, 3, 2)), Some((0, 1)));
+
+        let area = TextArea::new();
+        assert_eq!(area.rows(3), [(0, 0, 0)]);
+    }
+
+    #[test]
+    fn wide_graphemes_are_placed_by_columns() {
+        let mut area = TextArea::new().wrap();
+        area.set_content("漢字a漢");
+        assert_eq!(area.rows(3), [(0, 0, 1), (0, 1, 3), (0, 3, 4)]);
+
+        area.move_to((0, 2), false);
+        assert_eq!(area.cursor((0, 0, 3, 4)), Some((2, 1)));
+
+        // clicking on either half of a grapheme moves the cursor before it
+        area.render((0, 0, 3, 4), &mut InstructionBuffer::new(3, 4, None));
+        area.handle_event(Event::Mouse { button: 0, col: 1, row: 1, pressed: true });
+        assert_eq!(area.position(), (0, 1));
+
+        // without wrapping, the cursor scrolls by columns and only whole graphemes are shown
+        let mut area = TextArea::new();
+        area.set_content("漢字漢字");
+        area.move_to((0, 4), false);
+        let mut canvas = InstructionBuffer::new(3, 1, None);
+        area.render((0, 0, 3, 1), &mut canvas);
+        assert_eq!(area.cursor((0, 0, 3, 1)), Some((2, 0)));
+        assert_eq!(canvas.rasterize().into_iter().flatten().map(|(ch, _)| ch).collect::<Str>(), "字 ");
+    }
+}
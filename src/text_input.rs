@@ -0,0 +1,352 @@
+use crate::*;
+use crate::input_sequences::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Whether a grapheme is part of a word, for moving and deleting by words
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().any(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+/// The index of the start of the word before an index
+pub(crate) fn word_start(graphemes: &[&str], mut i: usize) -> usize {
+    while i > 0 && !is_word(graphemes[i - 1]) {
+        i -= 1;
+    }
+    while i > 0 && is_word(graphemes[i - 1]) {
+        i -= 1;
+    }
+    i
+}
+
+/// The index of the end of the word after an index
+pub(crate) fn word_end(graphemes: &[&str], mut i: usize) -> usize {
+    while i < graphemes.len() && !is_word(graphemes[i]) {
+        i += 1;
+    }
+    while i < graphemes.len() && is_word(graphemes[i]) {
+        i += 1;
+    }
+    i
+}
+
+/// Typed text, if the bytes of a key press are printable
+pub(crate) fn typed(bytes: &[u8]) -> Option<&str> {
+    str::from_utf8(bytes).ok().filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
+}
+
+/// The amount of graphemes from the start which fit entirely within some amount of columns
+pub(crate) fn fitting(widths: &[usize], mut width: usize) -> usize {
+    widths.iter().take_while(|&&w| width.checked_sub(w).map(|rest| width = rest).is_some()).count()
+}
+
+/// A single line text field.
+///
+/// Supports moving by graphemes and words, `HOME`/`END`, deleting with `BACKSPACE`, `DELETE`
+/// and `CTRL_BACKSPACE`, and the emacs bindings `CTRL_A`, `CTRL_E`, `CTRL_U`, `CTRL_K`, `CTRL_W`
+/// and `CTRL_Y` for pasting the last killed text.
+pub struct TextInput {
+    text: Str,
+    /// Index of the grapheme before which text is inserted
+    cursor: usize,
+    /// Index of the first grapheme shown, updated when rendering
+    scroll: std::cell::Cell<usize>,
+    killed: Str,
+    placeholder: Str,
+    mask: Option<char>,
+    style: Option<Style>,
+    placeholder_style: Style,
+}
+
+impl Default for TextInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self {
+            text: Str::new(),
+            cursor: 0,
+            scroll: std::cell::Cell::new(0),
+            killed: Str::new(),
+            placeholder: Str::new(),
+            mask: None,
+            style: None,
+            placeholder_style: Style::new().dim(),
+        }
+    }
+
+    /// Shown while the field is empty
+    pub fn placeholder(mut self, placeholder: impl Into<Str>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn placeholder_style(mut self, style: Style) -> Self {
+        self.placeholder_style = style;
+        self
+    }
+
+    /// Show every grapheme as a character, such as `*` for passwords
+    pub fn mask(mut self, ch: char) -> Self {
+        self.mask = Some(ch);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, moving the cursor to the end
+    pub fn set_text(&mut self, text: impl Into<Str>) {
+        self.text = text.into();
+        self.cursor = self.len();
+    }
+
+    /// Index of the grapheme before which text is inserted
+    pub fn position(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_position(&mut self, position: usize) {
+        self.cursor = position.min(self.len());
+    }
+
+    fn len(&self) -> usize {
+        self.text.graphemes(true).count()
+    }
+
+    /// The amount of columns every grapheme takes up when shown, which is that of the mask if set
+    fn widths(&self) -> Vec<usize> {
+        let mask = self.mask.map(|mask| utils::grapheme_width(&mask.to_string()));
+        self.text.graphemes(true).map(|grapheme| mask.unwrap_or_else(|| utils::grapheme_width(grapheme))).collect()
+    }
+
+    /// The first grapheme shown, keeping the grapheme at the cursor visible
+    fn scroll(&self, widths: &[usize], width: usize) -> usize {
+        let mut scroll = self.scroll.get().min(self.cursor);
+        let cursor_width = widths.get(self.cursor).copied().unwrap_or(1).max(1);
+        while scroll < self.cursor && widths[scroll..self.cursor].iter().sum::<usize>() + cursor_width > width {
+            scroll += 1;
+        }
+        scroll
+    }
+
+    /// The byte index of a grapheme index
+    fn byte_index(&self, i: usize) -> usize {
+        self.text.grapheme_indices(true).nth(i).map_or(self.text.len(), |(byte, _)| byte)
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        let byte = self.byte_index(self.cursor);
+        self.text.insert_str(byte, text);
+
+        // the inserted text may join the graphemes around it, such as a combining mark,
+        // so the cursor goes after the grapheme in which the inserted text ends
+        let end = byte + text.len();
+        self.cursor = self.text.grapheme_indices(true).take_while(|&(i, _)| i < end).count();
+    }
+
+    /// Remove the graphemes between two indices, returning them
+    fn remove(&mut self, start: usize, end: usize) -> Str {
+        let (start_byte, end_byte) = (self.byte_index(start), self.byte_index(end));
+        let removed = self.text[start_byte..end_byte].to_string();
+        self.text.replace_range(start_byte..end_byte, "");
+        self.cursor = start;
+        removed
+    }
+
+    /// Remove text, so that it can be pasted with `CTRL_Y`
+    fn kill(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.killed = self.remove(start, end);
+        }
+    }
+}
+
+impl Widget for TextInput {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        canvas.draw_hbar(col, row, width, " ", self.style.as_ref());
+
+        if self.text.is_empty() {
+            let placeholder = utils::truncate(&self.placeholder, width);
+            canvas.addstr(col, row, &placeholder, Some(&self.placeholder_style));
+            self.scroll.set(0);
+            return
+        }
+
+        // keep the cursor visible, with room for it after the last grapheme
+        let widths = self.widths();
+        let scroll = self.scroll(&widths, width as usize);
+        self.scroll.set(scroll);
+
+        // wide graphemes cut off at the end are left out
+        let count = fitting(&widths[scroll..], width as usize);
+
+        let shown: Str = match self.mask {
+            Some(mask) => std::iter::repeat_n(mask, count).collect(),
+            None => self.text.graphemes(true).skip(scroll).take(count).collect(),
+        };
+        canvas.addstr(col, row, &shown, self.style.as_ref());
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let bytes = match event {
+            Event::Key(bytes) => bytes,
+            Event::Paste(text) => {
+                self.insert(&text.replace(['\r', '\n'], " "));
+                return EventResult::Consumed
+            }
+            Event::Mouse { col, pressed: true, .. } => {
+                // the cursor goes before the grapheme covering the column
+                let scroll = self.scroll.get().min(self.len());
+                self.set_position(scroll + fitting(&self.widths()[scroll..], col as usize));
+                return EventResult::Consumed
+            }
+            _ => return EventResult::Ignored,
+        };
+
+        let bytes = &bytes[..];
+        let graphemes: Vec<_> = self.text.graphemes(true).collect();
+        let len = graphemes.len();
+        let word_start = word_start(&graphemes, self.cursor);
+        let word_end = word_end(&graphemes, self.cursor);
+
+        match bytes {
+            ARROW_LEFT => self.cursor = self.cursor.saturating_sub(1),
+            ARROW_RIGHT => self.cursor = (self.cursor + 1).min(len),
+            CTRL_ARROW_LEFT => self.cursor = word_start,
+            CTRL_ARROW_RIGHT => self.cursor = word_end,
+            CTRL_A => self.cursor = 0,
+            CTRL_E => self.cursor = len,
+            _ if HOME.contains(&bytes) => self.cursor = 0,
+            _ if END.contains(&bytes) => self.cursor = len,
+
+            BACKSPACE if self.cursor > 0 => { self.remove(self.cursor - 1, self.cursor); }
+            DELETE if self.cursor < len => { self.remove(self.cursor, self.cursor + 1); }
+            BACKSPACE | DELETE => {}
+            CTRL_BACKSPACE | CTRL_W => self.kill(word_start, self.cursor),
+            CTRL_U => self.kill(0, self.cursor),
+            CTRL_K => self.kill(self.cursor, len),
+            CTRL_Y => self.insert(&self.killed.clone()),
+
+            _ => match typed(bytes) {
+                Some(text) => self.insert(text),
+                None => return EventResult::Ignored,
+            },
+        }
+
+        EventResult::Consumed
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (1, 1)
+    }
+
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        let width = self.widths().iter().sum::<usize>().max(utils::display_width(&self.placeholder));
+        Some((width as Cell + 1, 1))
+    }
+
+    fn cursor(&self, (col, row, width, _): Area) -> Option<(Cell, Cell)> {
+        let widths = self.widths();
+        let scroll = self.scroll.get().min(self.cursor);
+        let offset = widths[scroll..self.cursor].iter().sum::<usize>().min(width.saturating_sub(1).into());
+        Some((col + offset as Cell, row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(input: &mut TextInput, bytes: &[u8]) {
+        input.handle_event(Event::Key(bytes.to_vec()));
+    }
+
+    #[test]
+    fn insert_and_move() {
+        let mut input = TextInput::new();
+        input.insert("hello");
+        key(&mut input, ARROW_LEFT);
+        key(&mut input, ARROW_LEFT);
+        input.insert("XY");
+        assert_eq!(input.text(), "helXYlo");
+        assert_eq!(input.position(), 5);
+
+        key(&mut input, BACKSPACE);
+        key(&mut input, DELETE);
+        assert_eq!(input.text(), "helXo");
+        assert_eq!(input.position(), 4);
+    }
+
+    #[test]
+    fn combining_marks_join_the_previous_grapheme() {
+        let mut input = TextInput::new();
+        input.insert("ae");
+        input.insert("\u{301}");
+        assert_eq!(input.position(), 2);
+
+        input.set_position(1);
+        input.insert("\u{301}");
+        assert_eq!(input.text(), "a\u{301}e\u{301}");
+        assert_eq!(input.position(), 1);
+
+        key(&mut input, DELETE);
+        assert_eq!(input.text(), "a\u{301}");
+    }
+
+    #[test]
+    fn kill_and_yank() {
+        let mut input = TextInput::new();
+        input.set_text("one two three");
+        key(&mut input, CTRL_W);
+        assert_eq!(input.text(), "one two ");
+
+        key(&mut input, CTRL_A);
+        key(&mut input, CTRL_ARROW_RIGHT);
+        key(&mut input, CTRL_K);
+        assert_eq!(input.text(), "one");
+
+        key(&mut input, CTRL_A);
+        key(&mut input, CTRL_Y);
+        assert_eq!(input.text(), " two one");
+        assert_eq!(input.position(), 5);
+    }
+
+    #[test]
+    fn wide_graphemes_are_not_cut() {
+        let shown = |input: &TextInput| {
+            let mut canvas = InstructionBuffer::new(4, 1, None);
+            input.render((0, 0, 4, 1), &mut canvas);
+            canvas.rasterize().into_iter().flatten().map(|(ch, _)| ch).collect::<Str>()
+        };
+
+        let mut input = TextInput::new();
+        input.set_text("漢字漢字漢字");
+        assert_eq!(input.preferred_size(), Some((13, 1)));
+
+        // the cursor at the end has room for itself after the last whole grapheme shown
+        assert_eq!(shown(&input), "字  ");
+        assert_eq!(input.cursor((0, 0, 4, 1)), Some((2, 0)));
+
+        key(&mut input, CTRL_A);
+        assert_eq!(shown(&input), "漢字");
+        assert_eq!(input.cursor((0, 0, 4, 1)), Some((0, 0)));
+
+        // clicking on either half of a grapheme moves the cursor before it
+        input.handle_event(Event::Mouse { button: 0, col: 3, row: 0, pressed: true });
+        assert_eq!(input.position(), 1);
+        assert_eq!(input.cursor((0, 0, 4, 1)), Some((2, 0)));
+    }
+}
@@ -0,0 +1,420 @@
+use crate::*;
+use crate::input_sequences::*;
+
+/// Guides drawn before nodes, for a node with siblings after it, the last node, a line
+/// past a node with siblings after it, and nothing past the last node
+const GUIDES: [Static; 4] = ["├─ ", "└─ ", "│  ", "   "];
+
+/// Drawn before the label of nodes which have or may have children, when collapsed and expanded
+const MARKERS: [Static; 2] = ["▸ ", "▾ "];
+
+/// Loads the children of a node when it is first expanded,
+/// given the node and its ancestors from the root down
+pub type Loader = Box<dyn FnMut(&[&TreeNode]) -> Vec<TreeNode>>;
+
+/// A node of a `Tree`
+#[derive(Clone, PartialEq)]
+pub struct TreeNode {
+    pub label: Line,
+    /// `None` until the children are loaded
+    pub children: Option<Vec<TreeNode>>,
+    pub expanded: bool,
+}
+
+impl TreeNode {
+    /// A node without children
+    pub fn leaf(label: impl Into<Line>) -> Self {
+        Self {
+            label: label.into(),
+            children: Some(Vec::new()),
+            expanded: false,
+        }
+    }
+
+    pub fn branch(label: impl Into<Line>, children: Vec<TreeNode>) -> Self {
+        Self {
+            label: label.into(),
+            children: Some(children),
+            expanded: false,
+        }
+    }
+
+    /// A node whose children are loaded by the loader of the tree when it is expanded
+    pub fn lazy(label: impl Into<Line>) -> Self {
+        Self {
+            label: label.into(),
+            children: None,
+            expanded: false,
+        }
+    }
+
+    pub fn expanded(mut self) -> Self {
+        self.expanded = true;
+        self
+    }
+
+    /// Whether the node has or may have children
+    pub fn expandable(&self) -> bool {
+        self.children.as_ref().is_none_or(|children| !children.is_empty())
+    }
+}
+
+/// A node and its ancestors, from the root down
+fn ancestors<'a>(roots: &'a [TreeNode], path: &[usize]) -> Vec<&'a TreeNode> {
+    let mut nodes: Vec<&TreeNode> = Vec::new();
+    for i in path {
+        let siblings = match nodes.last() {
+            Some(parent) => parent.children.as_deref().unwrap_or_default(),
+            None => roots,
+        };
+        match siblings.get(*i) {
+            Some(node) => nodes.push(node),
+            None => return Vec::new(),
+        }
+    }
+    nodes
+}
+
+/// A shown node, with its path and the guides before it
+struct Row {
+    path: Vec<usize>,
+    guides: Str,
+}
+
+/// Collapsible nodes, one of which is selected.
+///
+/// `ARROW_RIGHT` expands the selected node or moves to its first child, and `ARROW_LEFT`
+/// collapses it or moves to its parent. `RETURN` toggles it.
+/// Moved through like a `List` otherwise.
+pub struct Tree {
+    roots: Vec<TreeNode>,
+    loader: Option<Loader>,
+    /// Path of the selected node, as the index among its siblings at every depth
+    selected: Vec<usize>,
+    /// The first row shown, updated when rendering
+    scroll: std::cell::Cell<usize>,
+    /// The height when last rendered, used for moving by pages
+    height: std::cell::Cell<Cell>,
+    guide_style: Option<Style>,
+    highlight_style: Style,
+}
+
+impl Tree {
+    pub fn new(roots: Vec<TreeNode>) -> Self {
+        Self {
+            selected: if roots.is_empty() { Vec::new() } else { vec![0] },
+            roots,
+            loader: None,
+            scroll: std::cell::Cell::new(0),
+            height: std::cell::Cell::new(1),
+            guide_style: None,
+            highlight_style: Style::new().fg(Color::Black).bg(Color::White),
+        }
+    }
+
+    /// Used to load the children of lazy nodes
+    pub fn loader(mut self, loader: impl FnMut(&[&TreeNode]) -> Vec<TreeNode> + 'static) -> Self {
+        self.loader = Some(Box::new(loader));
+        self
+    }
+
+    pub fn guide_style(mut self, style: Style) -> Self {
+        self.guide_style = Some(style);
+        self
+    }
+
+    /// Style of the selected node
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    pub fn roots(&self) -> &[TreeNode] {
+        &self.roots
+    }
+
+    pub fn roots_mut(&mut self) -> &mut Vec<TreeNode> {
+        &mut self.roots
+    }
+
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        ancestors(&self.roots, path).pop()
+    }
+
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter().try_fold(self.roots.get_mut(*first)?, |node, i| node.children.as_mut()?.get_mut(*i))
+    }
+
+    /// Path of the selected node, which is empty if there are no nodes
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    pub fn selected_node(&self) -> Option<&TreeNode> {
+        self.node(&self.selected)
+    }
+
+    /// Select a node, expanding its ancestors so that it is shown
+    pub fn select(&mut self, path: &[usize]) {
+        if self.node(path).is_none() {
+            return
+        }
+        for depth in 1..path.len() {
+            self.expand(&path[..depth]);
+        }
+        self.selected = path.to_vec();
+    }
+
+    /// Expand a node, loading its children first if needed
+    pub fn expand(&mut self, path: &[usize]) {
+        let loaded = match self.node(path) {
+            Some(node) if node.children.is_none() => {
+                let nodes = ancestors(&self.roots, path);
+                Some(self.loader.as_mut().map_or_else(Vec::new, |loader| loader(&nodes)))
+            }
+            Some(..) => None,
+            None => return,
+        };
+
+        if let Some(node) = self.node_mut(path) {
+            if let Some(children) = loaded {
+                node.children = Some(children);
+            }
+            node.expanded = true;
+        }
+    }
+
+    pub fn collapse(&mut self, path: &[usize]) {
+        if let Some(node) = self.node_mut(path) {
+            node.expanded = false;
+        }
+
+        // keep the selection shown
+        if self.selected.len() > path.len() && self.selected.starts_with(path) {
+            self.selected.truncate(path.len());
+        }
+    }
+
+    pub fn toggle(&mut self, path: &[usize]) {
+        match self.node(path) {
+            Some(node) if node.expanded => self.collapse(path),
+            Some(..) => self.expand(path),
+            None => {}
+        }
+    }
+
+    /// The shown nodes, from the top down
+    fn rows(&self) -> Vec<Row> {
+        fn walk(nodes: &[TreeNode], path: &mut Vec<usize>, guides: &str, rows: &mut Vec<Row>) {
+            for (i, node) in nodes.iter().enumerate() {
+                let last = i + 1 == nodes.len();
+                // roots are drawn without guides
+                let (own, below) = match path.is_empty() {
+                    true => ("", ""),
+                    false if last => (GUIDES[1], GUIDES[3]),
+                    false => (GUIDES[0], GUIDES[2]),
+                };
+
+                path.push(i);
+                rows.push(Row { path: path.clone(), guides: format!("{guides}{own}") });
+                if node.expanded && let Some(children) = &node.children {
+                    walk(children, path, &format!("{guides}{below}"), rows);
+                }
+                path.pop();
+            }
+        }
+
+        let mut rows = Vec::new();
+        walk(&self.roots, &mut Vec::new(), "", &mut rows);
+        rows
+    }
+
+    /// Move the selection by some amount of shown nodes
+    fn move_by(&mut self, amount: isize) {
+        let rows = self.rows();
+        let Some(current) = rows.iter().position(|row| row.path == self.selected) else {
+            return
+        };
+        let index = current.saturating_add_signed(amount).min(rows.len() - 1);
+        self.selected = rows[index].path.clone();
+    }
+}
+
+impl Widget for Tree {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        let rows = self.rows();
+        let selected = rows.iter().position(|row| row.path == self.selected).unwrap_or(0);
+
+        // keep the selected node visible
+        let visible = height as usize;
+        let mut scroll = self.scroll.get().min(selected);
+        if selected >= scroll + visible {
+            scroll = selected + 1 - visible;
+        }
+        let scroll = scroll.min(rows.len().saturating_sub(visible));
+        self.scroll.set(scroll);
+        self.height.set(height);
+
+        let overflows = rows.len() > visible;
+        let width = if overflows { width - 1 } else { width };
+
+        canvas.clear_rect(col, row, width, height);
+
+        for (i, tree_row) in rows.iter().enumerate().skip(scroll).take(visible) {
+            let Some(node) = self.node(&tree_row.path) else {
+                continue
+            };
+            let node_row = row + (i - scroll) as Cell;
+            let highlighted = i == selected;
+
+            let mut line = Line::new(vec![Span { content: tree_row.guides.clone(), style: self.guide_style.clone() }]);
+            if node.expandable() {
+                line.spans.push(Span::new(MARKERS[node.expanded as usize]));
+            }
+            let indent = line.width();
+
+            if highlighted {
+                canvas.draw_hbar(col + indent.min(width), node_row, width.saturating_sub(indent), " ",
+                    Some(&self.highlight_style));
+            }
+
+            for span in &node.label.spans {
+                let style = if highlighted { Some(self.highlight_style.clone()) } else { span.style.clone() };
+                line.spans.push(Span { content: span.content.clone(), style });
+            }
+            canvas.addline(col, node_row, width, &line);
+        }
+
+        list::draw_scrollbar(canvas, col + width, row, height, scroll, rows.len(), self.guide_style.as_ref());
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        let bytes = match event {
+            Event::Key(bytes) => bytes,
+            Event::Mouse { row, pressed: true, .. } => {
+                let rows = self.rows();
+                let Some(clicked) = rows.get(self.scroll.get() + row as usize) else {
+                    return EventResult::Ignored
+                };
+
+                // clicking the selected node toggles it
+                if clicked.path == self.selected {
+                    self.toggle(&clicked.path);
+                } else {
+                    self.selected = clicked.path.clone();
+                }
+                return EventResult::Consumed
+            }
+            _ => return EventResult::Ignored,
+        };
+
+        if self.selected.is_empty() {
+            return EventResult::Ignored
+        }
+
+        let bytes = &bytes[..];
+        let page = self.height.get().max(1) as isize;
+        let selected = self.selected.clone();
+
+        match bytes {
+            ARROW_UP => self.move_by(-1),
+            ARROW_DOWN => self.move_by(1),
+            PG_UP => self.move_by(-page),
+            PG_DOWN => self.move_by(page),
+            _ if HOME.contains(&bytes) => self.move_by(isize::MIN),
+            _ if END.contains(&bytes) => self.move_by(isize::MAX),
+            RETURN => self.toggle(&selected),
+
+            ARROW_RIGHT => match self.node(&selected) {
+                Some(TreeNode { expanded: true, children: Some(children), .. }) if !children.is_empty() => {
+                    self.selected.push(0)
+                }
+                // also loads the children of lazy nodes created expanded
+                Some(node) if node.expandable() => self.expand(&selected),
+                _ => {}
+            },
+            ARROW_LEFT => match self.node(&selected) {
+                Some(node) if node.expanded => self.collapse(&selected),
+                _ if selected.len() > 1 => { self.selected.pop(); }
+                _ => {}
+            },
+
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (1, 1)
+    }
+
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        let rows = self.rows();
+        let width = rows.iter()
+            .filter_map(|row| {
+                let node = self.node(&row.path)?;
+                let marker = if node.expandable() { 2 } else { 0 };
+                Some(Line::from(row.guides.as_str()).width() + marker + node.label.width())
+            })
+            .max().unwrap_or(0);
+        Some((width, rows.len() as Cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(tree: &mut Tree, bytes: &[u8]) {
+        tree.handle_event(Event::Key(bytes.to_vec()));
+    }
+
+    #[test]
+    fn move_through_nodes() {
+        let mut tree = Tree::new(vec![
+            TreeNode::branch("a", vec![TreeNode::leaf("b"), TreeNode::leaf("c")]),
+            TreeNode::leaf("d"),
+        ]);
+
+        key(&mut tree, ARROW_DOWN);
+        assert_eq!(tree.selected(), [1]);
+        key(&mut tree, ARROW_UP);
+        key(&mut tree, ARROW_RIGHT);
+        key(&mut tree, ARROW_RIGHT);
+        key(&mut tree, ARROW_DOWN);
+        assert_eq!(tree.selected(), [0, 1]);
+
+        key(&mut tree, ARROW_LEFT);
+        key(&mut tree, ARROW_LEFT);
+        assert_eq!(tree.selected(), [0]);
+        assert!(!tree.roots()[0].expanded);
+    }
+
+    #[test]
+    fn expanded_lazy_nodes_load_their_children() {
+        let mut tree = Tree::new(vec![TreeNode::lazy("a").expanded(), TreeNode::lazy("b").expanded()])
+            .loader(|nodes| match nodes[0].label.spans[0].content.as_str() {
+                "a" => vec![TreeNode::leaf("child")],
+                _ => Vec::new(),
+            });
+
+        key(&mut tree, ARROW_RIGHT);
+        assert_eq!(tree.selected(), [0]);
+        key(&mut tree, ARROW_RIGHT);
+        assert_eq!(tree.selected(), [0, 0]);
+
+        key(&mut tree, ARROW_DOWN);
+        assert_eq!(tree.selected(), [1]);
+        key(&mut tree, ARROW_RIGHT);
+        key(&mut tree, ARROW_RIGHT);
+        assert_eq!(tree.selected(), [1]);
+        key(&mut tree, ARROW_UP);
+        assert_eq!(tree.selected(), [0, 0]);
+    }
+}
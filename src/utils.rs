@@ -1,7 +1,11 @@
 #![allow(unused_imports)]
 
 use std::io::{self, Read, Write, stdin, stdout};
+use std::sync::Mutex;
 use crate::control_sequences::*;
+use crate::text::{Chunk, chunks};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Flush stdout, ignoring errors
 pub fn flush() {
@@ -21,10 +25,24 @@ pub fn flush() {
   */
 pub fn read_bytes<const A: usize>() -> Result<Box<[u8]>, Box<dyn std::error::Error>> {
     let mut bytes = [0; A];
+
+    // input read while waiting for a reply from the terminal comes first
+    let mut pending = PENDING.lock().unwrap_or_else(|error| error.into_inner());
+    if !pending.is_empty() {
+        let len = pending.len().min(A);
+        bytes[..len].copy_from_slice(&pending[..len]);
+        pending.drain(..len);
+        return Ok(strip_bytes(&bytes))
+    }
+    drop(pending);
+
     stdin().read(&mut bytes)?;
     Ok(strip_bytes(&bytes))
 }
 
+/// Input read by `cursor_position` which was not part of the reply, returned by `read_bytes`
+static PENDING: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
 /// Block until Ctrl-C is pressed
 pub fn block_until_interrupt() {
     loop {
@@ -43,23 +61,226 @@ pub fn strip_bytes(bytes: &[u8]) -> Box<[u8]> {
     bytes.iter().map(|byte| *byte).take_while(|byte| *byte != 0).collect::<Vec<_>>().into()
 }
 
+/// The amount of columns a grapheme takes up in a terminal, such as 2 for CJK characters and
+/// most emoji, and 0 for control characters
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.chars().all(char::is_control) {
+        0
+    } else {
+        grapheme.width()
+    }
+}
+
+/// The amount of columns a string without escape sequences takes up, see `grapheme_width`
+pub fn display_width(string: &str) -> usize {
+    string.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Truncate a string to some amount of columns, ending it with an ellipsis if it was cut
+pub fn truncate(string: &str, width: u16) -> String {
+    let width = width as usize;
+
+    if display_width(string) <= width {
+        return string.to_string()
+    } else if width == 0 {
+        return String::new()
+    }
+
+    let mut result = String::new();
+    let mut used = 0;
+    for grapheme in string.graphemes(true) {
+        used += grapheme_width(grapheme);
+        if used > width - 1 {
+            break
+        }
+        result.push_str(grapheme);
+    }
+
+    result.push('…');
+    result
+}
+
+/// Remove every escape sequence from a string, such as the ones from `Style::as_string`
+pub fn strip_escapes(string: &str) -> String {
+    chunks(string).into_iter()
+        .filter_map(|chunk| match chunk {
+            Chunk::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The amount of columns taken up when printing a string, ignoring escape sequences
+/// and control characters
+pub fn visible_width(string: &str) -> usize {
+    display_width(&strip_escapes(string))
+}
+
+/// Like `truncate`, but ignoring escape sequences when measuring and keeping all of them,
+/// so that styles set after the cut, such as a reset, still apply
+pub fn truncate_escaped(string: &str, width: u16) -> String {
+    let width = width as usize;
+    let len = visible_width(string);
+    let mut left = if len > width { width.saturating_sub(1) } else { width };
+    let ellipsis = len > width && width > 0;
+    let mut cut = false;
+    let mut result = String::new();
+
+    for chunk in chunks(string) {
+        match chunk {
+            Chunk::Text(text) => {
+                for grapheme in text.graphemes(true) {
+                    let width = grapheme_width(grapheme);
+                    if grapheme.chars().all(char::is_control) {
+                        result.push_str(grapheme);
+                    } else if width <= left && !cut {
+                        result.push_str(grapheme);
+                        left -= width;
+                    } else if !cut {
+                        // nothing after the first grapheme that does not fit is kept
+                        cut = true;
+                        if ellipsis {
+                            result.push('…');
+                        }
+                    }
+                }
+            }
+            Chunk::Csi(params, end) => {
+                result.push_str("\x1b[");
+                result.push_str(params);
+                result.push(end);
+            }
+            Chunk::Other(sequence) => result.push_str(sequence),
+        }
+    }
+
+    result
+}
+
+/// Ask the terminal where the cursor is, as a column and row starting at 0.
+/// Raw mode must be enabled. Input typed before the reply arrives is kept for `read_bytes`.
+///
+/// This blocks until the terminal replies, so it never returns if stdin is not a terminal or
+/// the terminal does not answer the query.
+pub fn cursor_position() -> Result<(u16, u16), io::Error> {
+    print!("{CUR_QUERY}");
+    flush();
+
+    let mut input = Vec::new();
+    let mut byte = [0];
+    let reply = loop {
+        if let Err(error) = stdin().read_exact(&mut byte) {
+            PENDING.lock().unwrap_or_else(|error| error.into_inner()).extend(input);
+            return Err(error)
+        }
+        input.push(byte[0]);
+
+        if byte[0] == b'R' && let Some(reply) = position_reply(&input) {
+            break reply
+        }
+    };
+
+    let (start, col, row) = reply;
+    input.truncate(start);
+    PENDING.lock().unwrap_or_else(|error| error.into_inner()).extend(input);
+    Ok((col, row))
+}
+
+/// The start of a cursor position reply at the end of some input, and the column and row
+/// starting at 0
+fn position_reply(input: &[u8]) -> Option<(usize, u16, u16)> {
+    let start = input.windows(2).rposition(|window| window == b"\x1b[")?;
+    let (row, col) = str::from_utf8(input[start + 2..].strip_suffix(b"R")?).ok()?.split_once(';')?;
+
+    let number = |number: &str| number.bytes().all(|byte| byte.is_ascii_digit())
+        .then(|| number.parse::<u16>().ok()).flatten();
+
+    Some((start, number(col)?.saturating_sub(1), number(row)?.saturating_sub(1)))
+}
+
 #[cfg(feature = "crossterm")]
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
 
-/// Enable raw mode and clear screen, without flushing stdout
+/// Enable raw mode, switch to the alternate screen and clear it
 #[cfg(feature = "crossterm")]
 pub fn init_term() -> Result<(), io::Error> {
     let res = enable_raw_mode();
-    print!("{ERASE_SCREEN}{CUR_HOME}{RESET}");
+    print!("{ALT_SCREEN_ENTER}{ERASE_SCREEN}{CUR_HOME}{RESET}");
     flush();
     res
 }
 
-/// Disable raw mode, clear screen, show cursor, and flush stdout
+/// Disable raw mode, switch back to the main screen, show cursor, and flush stdout
 #[cfg(feature = "crossterm")]
 pub fn restore_term() -> Result<(), io::Error> {
     let res = disable_raw_mode();
-    print!("{RESET}{ERASE_SCREEN}{CUR_HOME}{CUR_SHOW}");
+    print!("{RESET}{ALT_SCREEN_LEAVE}{CUR_SHOW}");
     flush();
     res
 }
+
+/// Enable raw mode and reserve lines below the cursor, without clearing the screen
+#[cfg(feature = "crossterm")]
+pub fn init_inline(height: u16) -> Result<crate::Viewport, io::Error> {
+    enable_raw_mode()?;
+    crate::Viewport::new(height).inspect_err(|_| {
+        let _ = disable_raw_mode();
+    })
+}
+
+/// Move below the viewport, leaving its last frame in the scrollback, disable raw mode,
+/// show cursor, and flush stdout
+#[cfg(feature = "crossterm")]
+pub fn restore_inline(viewport: crate::Viewport) -> Result<(), io::Error> {
+    viewport.finish();
+    let res = disable_raw_mode();
+    print!("{RESET}{CUR_SHOW}");
+    flush();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(grapheme_width("a"), 1);
+        assert_eq!(grapheme_width("漢"), 2);
+        assert_eq!(grapheme_width("e\u{301}"), 1);
+        assert_eq!(grapheme_width("\t"), 0);
+        assert_eq!(display_width("漢字 ok"), 7);
+
+        assert_eq!(visible_width("\x1b[1;31m漢字\x1b[0m ok\n"), 7);
+        assert_eq!(visible_width("\x1b]0;title\x07plain"), 5);
+    }
+
+    #[test]
+    fn truncate_by_columns() {
+        assert_eq!(truncate("hello", 5), "hello");
+        assert_eq!(truncate("hello", 4), "hel…");
+        assert_eq!(truncate("漢字漢字", 8), "漢字漢字");
+        assert_eq!(truncate("漢字漢字", 6), "漢字…");
+        // a wide grapheme which does not fit is left out
+        assert_eq!(truncate("漢字漢字", 5), "漢字…");
+        assert_eq!(truncate("hello", 0), "");
+    }
+
+    #[test]
+    fn find_position_replies() {
+        assert_eq!(position_reply(b"\x1b[12;40R"), Some((0, 39, 11)));
+        // keys typed before the reply
+        assert_eq!(position_reply(b"ab\x1b[A\x1b[1;1R"), Some((5, 0, 0)));
+        assert_eq!(position_reply(b"\x1b[AR"), None);
+        assert_eq!(position_reply(b"\x1b[;1R"), None);
+        assert_eq!(position_reply(b"R"), None);
+    }
+
+    #[test]
+    fn truncate_keeping_escapes() {
+        assert_eq!(truncate_escaped("\x1b[31mhello\x1b[0m", 4), "\x1b[31mhel…\x1b[0m");
+        assert_eq!(truncate_escaped("\x1b[31m漢字漢字\x1b[0m", 6), "\x1b[31m漢字…\x1b[0m");
+        assert_eq!(truncate_escaped("漢字a漢字", 5), "漢字…");
+        assert_eq!(truncate_escaped("\x1b[31mhi\x1b[0m", 2), "\x1b[31mhi\x1b[0m");
+    }
+}
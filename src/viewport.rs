@@ -0,0 +1,66 @@
+use crate::*;
+use std::io;
+
+/// Lines below the prompt that a canvas is drawn in, instead of taking over the whole screen.
+/// See `utils::init_inline` and `utils::restore_inline`.
+pub struct Viewport {
+    row: Cell,
+    height: Cell,
+}
+
+impl Viewport {
+    /// Reserve lines starting at the line of the cursor, or the next line if the cursor is not at
+    /// the start of it, scrolling the terminal if needed. Raw mode must be enabled.
+    /// Like `utils::cursor_position`, this blocks until the terminal replies.
+    pub fn new(height: Cell) -> Result<Self, io::Error> {
+        let height = height.max(1);
+        let (col, _) = utils::cursor_position()?;
+
+        let mut output = Str::new();
+        if col > 0 {
+            output.push_str("\r\n");
+        }
+        output.push_str(&"\n".repeat(height as usize - 1));
+        if height > 1 {
+            output.push_str(&formatf!("{CUR_UP}", height - 1));
+        }
+        print!("{output}{CUR_COL_HOME}");
+
+        let (_, row) = utils::cursor_position()?;
+        Ok(Self { row, height })
+    }
+
+    /// The first row of the viewport, starting at 0
+    pub fn row(&self) -> Cell {
+        self.row
+    }
+
+    pub fn height(&self) -> Cell {
+        self.height
+    }
+
+    /// Erase the lines of the viewport
+    pub fn clear(&self) {
+        let mut output = Str::new();
+        for row in self.row..self.row + self.height {
+            output.push_str(&formatf!("{CUR_SET}", row + 1, 1));
+            output.push_str(ERASE_LINE);
+        }
+        print!("{output}");
+    }
+
+    /// Draw a canvas in the viewport, replacing what was drawn before, and flush stdout.
+    /// The canvas should be no higher than the viewport.
+    pub fn draw(&self, canvas: &(impl Canvas + ?Sized)) {
+        self.clear();
+        print!("{}{RESET}", canvas.render(0, self.row));
+        utils::flush();
+    }
+
+    /// Move the cursor to the start of the line below the viewport, so that what was drawn is left
+    /// in the scrollback
+    pub fn finish(self) {
+        print!("{}\r\n", formatf!("{CUR_SET}", self.row + self.height, 1));
+        utils::flush();
+    }
+}
@@ -0,0 +1,159 @@
+use crate::*;
+use crate::input_sequences::*;
+
+/// Column, row, width and height of the part of a canvas a widget is drawn in
+pub type Area = (Cell, Cell, Cell, Cell);
+
+/// Input passed to widgets
+#[derive(Clone, PartialEq, Debug)]
+pub enum Event {
+    /// The bytes of a key press, to be compared with the sequences in `input_sequences`
+    Key(Vec<u8>),
+    /// Text pasted while bracketed paste is enabled
+    Paste(Str),
+    /// A mouse button pressed or released, with the column and row starting at 0
+    Mouse {
+        button: u8,
+        col: Cell,
+        row: Cell,
+        pressed: bool,
+    },
+    /// The new width and height of the terminal
+    Resize(Cell, Cell),
+}
+
+impl Event {
+    /// Interpret bytes read from stdin, such as with `utils::read_bytes`.
+    /// Anything which is not a paste or a mouse event is a key press.
+    pub fn parse(bytes: &[u8]) -> Self {
+        if let Some(rest) = bytes.strip_prefix(PASTE_START) {
+            let end = rest.windows(PASTE_END.len())
+                .position(|window| window == PASTE_END)
+                .unwrap_or(rest.len());
+            return Self::Paste(String::from_utf8_lossy(&rest[..end]).into_owned())
+        }
+
+        if let Some(rest) = bytes.strip_prefix(MOUSE_SGR)
+                && let Some((&end, rest)) = rest.split_last()
+                && (end == b'M' || end == b'm') {
+
+            let numbers: Vec<_> = str::from_utf8(rest).unwrap_or_default()
+                .split(';')
+                .map(|number| number.parse::<u16>().ok())
+                .collect();
+
+            if let [Some(button), Some(col), Some(row)] = numbers[..] {
+                return Self::Mouse {
+                    button: button as u8,
+                    col: col.saturating_sub(1),
+                    row: row.saturating_sub(1),
+                    pressed: end == b'M',
+                }
+            }
+        }
+
+        Self::Key(bytes.to_vec())
+    }
+}
+
+/// Whether a widget made use of an event
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EventResult {
+    Consumed,
+    Ignored,
+}
+
+/// Something drawn in an area, which may react to input.
+/// Widgets can be used as the panes of a `Paner`, see `Paner::render_widgets`.
+pub trait Widget {
+    /// Draw the widget, without drawing outside of the area
+    fn render(&self, area: Area, canvas: &mut dyn Canvas);
+
+    fn handle_event(&mut self, _event: Event) -> EventResult {
+        EventResult::Ignored
+    }
+
+    /// The smallest width and height the widget can be drawn in
+    fn min_size(&self) -> (Cell, Cell) {
+        (0, 0)
+    }
+
+    /// The width and height the widget would like to have, if any
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        None
+    }
+
+    /// Where the terminal cursor should be shown when the widget is focused,
+    /// relative to the canvas
+    fn cursor(&self, _area: Area) -> Option<(Cell, Cell)> {
+        None
+    }
+}
+
+impl<W: Widget + ?Sized> Widget for Box<W> {
+    fn render(&self, area: Area, canvas: &mut dyn Canvas) {
+        (**self).render(area, canvas)
+    }
+
+    fn handle_event(&mut self, event: Event) -> EventResult {
+        (**self).handle_event(event)
+    }
+
+    fn min_size(&self) -> (Cell, Cell) {
+        (**self).min_size()
+    }
+
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        (**self).preferred_size()
+    }
+
+    fn cursor(&self, area: Area) -> Option<(Cell, Cell)> {
+        (**self).cursor(area)
+    }
+}
+
+impl Widget for Text {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        canvas.addlines(col, row, width, height, self);
+    }
+
+    fn preferred_size(&self) -> Option<(Cell, Cell)> {
+        Some((self.width(), self.height()))
+    }
+}
+
+impl Widget for Sparkline<'_> {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        Sparkline::render(self, canvas, col, row, width, height);
+    }
+}
+
+impl Widget for BarChart {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        BarChart::render(self, canvas, col, row, width, height);
+    }
+}
+
+impl Widget for LineChart {
+    fn render(&self, (col, row, width, height): Area, canvas: &mut dyn Canvas) {
+        LineChart::render(self, canvas, col, row, width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_events() {
+        assert_eq!(Event::parse(b"\x1b[<0;5;3M"), Event::Mouse { button: 0, col: 4, row: 2, pressed: true });
+        assert_eq!(Event::parse(b"\x1b[<2;1;1m"), Event::Mouse { button: 2, col: 0, row: 0, pressed: false });
+        assert_eq!(Event::parse(b"\x1b[200~a\nb\x1b[201~"), Event::Paste("a\nb".to_string()));
+        // a paste cut off by the size of the read
+        assert_eq!(Event::parse(b"\x1b[200~abc"), Event::Paste("abc".to_string()));
+
+        assert_eq!(Event::parse(b"\x1b[<0;5M"), Event::Key(b"\x1b[<0;5M".to_vec()));
+        assert_eq!(Event::parse(ARROW_UP), Event::Key(ARROW_UP.to_vec()));
+        assert_eq!(Event::parse(b"q"), Event::Key(b"q".to_vec()));
+    }
+}